@@ -0,0 +1,150 @@
+/// The name of the newtype struct [`bitvec`] serializes bit sequences as, by which the
+/// [`Serializer`](crate::Serializer) recognizes them.
+pub(crate) const NAME: &str = "$serde_beve::BoolArray";
+
+/// Packs booleans into the bytes of a boolean array, eight to a byte, starting from the most
+/// significant bit of each. Unused bits of the last byte are left unset.
+pub(crate) fn pack(values: impl IntoIterator<Item = bool>) -> Vec<u8> {
+    let values = values.into_iter();
+    let mut bytes = Vec::with_capacity(values.size_hint().0.div_ceil(8));
+    for (i, v) in values.enumerate() {
+        if i % 8 == 0 {
+            bytes.push(0);
+        }
+        *bytes.last_mut().unwrap() |= (v as u8) << (7 - i % 8);
+    }
+    bytes
+}
+
+/// Reads the boolean at `index` from the byte of a boolean array that holds it.
+pub(crate) const fn unpack(byte: u8, index: usize) -> bool {
+    byte & (0x80 >> (index % 8)) != 0
+}
+
+/// Serializes [`BitSlice`](::bitvec::slice::BitSlice)s as boolean arrays, for use with
+/// `#[serde(with)]`.
+///
+/// The bits are copied into the packed bytes of the array as a whole, rather than one boolean at
+/// a time. Other formats see a tuple of the number of bits and their bytes, packed as BEVE packs
+/// them.
+///
+/// ```ignore
+/// # use serde::{Deserialize, Serialize};
+/// use bitvec::vec::BitVec;
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Mask {
+///     #[serde(with = "serde_beve::bitvec")]
+///     bits: BitVec,
+/// }
+/// ```
+#[cfg(feature = "bitvec")]
+pub mod bitvec {
+    use super::NAME;
+    use ::bitvec::{order::BitOrder, order::Msb0, slice::BitSlice, store::BitStore, vec::BitVec};
+    use serde::{
+        Deserialize, Deserializer, Serialize, Serializer,
+        de::{Error as _, SeqAccess, Visitor},
+    };
+    use std::{fmt, marker::PhantomData};
+
+    pub fn serialize<T, O, S>(bits: &BitSlice<T, O>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: BitStore,
+        O: BitOrder,
+        S: Serializer,
+    {
+        // BEVE's bit order, which bitvec can copy into in bulk
+        let mut packed = BitVec::<u8, Msb0>::with_capacity(bits.len());
+        packed.extend_from_bitslice(bits);
+        packed.set_uninitialized(false);
+        let bytes = packed.into_vec();
+
+        serializer.serialize_newtype_struct(NAME, &(bits.len() as u64, Packed(&bytes)))
+    }
+
+    pub fn deserialize<'de, T, O, D>(deserializer: D) -> Result<BitVec<T, O>, D::Error>
+    where
+        T: BitStore,
+        O: BitOrder,
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(NAME, BitsVisitor(PhantomData))
+    }
+
+    struct Packed<'a>(&'a [u8]);
+
+    impl Serialize for Packed<'_> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_bytes(self.0)
+        }
+    }
+
+    struct BitsVisitor<T, O>(PhantomData<(T, O)>);
+
+    impl<'de, T: BitStore, O: BitOrder> Visitor<'de> for BitsVisitor<T, O> {
+        type Value = BitVec<T, O>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a boolean array")
+        }
+
+        fn visit_newtype_struct<D: Deserializer<'de>>(
+            self,
+            deserializer: D,
+        ) -> Result<Self::Value, D::Error> {
+            deserializer.deserialize_tuple(2, self)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let len: u64 = seq
+                .next_element()?
+                .ok_or_else(|| A::Error::invalid_length(0, &self))?;
+            let PackedBuf(bytes) = seq
+                .next_element()?
+                .ok_or_else(|| A::Error::invalid_length(1, &self))?;
+
+            let len = usize::try_from(len).map_err(|_| A::Error::custom("too many bits"))?;
+            if bytes.len() != len.div_ceil(8) {
+                return Err(A::Error::invalid_length(
+                    bytes.len(),
+                    &"one byte per eight bits",
+                ));
+            }
+            let packed = BitSlice::<u8, Msb0>::from_slice(&bytes);
+            let mut bits = BitVec::with_capacity(len);
+            bits.extend_from_bitslice(&packed[..len]);
+            Ok(bits)
+        }
+    }
+
+    struct PackedBuf(Vec<u8>);
+
+    impl<'de> Deserialize<'de> for PackedBuf {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_byte_buf(PackedVisitor)
+        }
+    }
+
+    struct PackedVisitor;
+
+    impl<'de> Visitor<'de> for PackedVisitor {
+        type Value = PackedBuf;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("packed bits")
+        }
+
+        fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+            Ok(PackedBuf(v.to_vec()))
+        }
+
+        fn visit_byte_buf<E: serde::de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+            Ok(PackedBuf(v))
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+            Vec::deserialize(serde::de::value::SeqAccessDeserializer::new(seq)).map(PackedBuf)
+        }
+    }
+}
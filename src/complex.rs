@@ -0,0 +1,146 @@
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{Error as _, SeqAccess, Visitor},
+};
+use std::{fmt, marker::PhantomData};
+
+/// The name of the newtype struct a [`Complex`] serializes as, by which the
+/// [`Serializer`](crate::Serializer) recognizes it.
+pub(crate) const NAME: &str = "$serde_beve::Complex";
+
+/// A complex number, serialized using BEVE's
+/// [complex extension](https://github.com/beve-org/beve/blob/main/extensions.md).
+///
+/// Any numeric type can be used for the parts. Sequences of complex numbers are written as complex
+/// arrays.
+///
+/// Other formats see a tuple of the real and imaginary parts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Complex<T> {
+    pub re: T,
+    pub im: T,
+}
+
+impl<T> Complex<T> {
+    pub const fn new(re: T, im: T) -> Self {
+        Self { re, im }
+    }
+}
+
+impl<T: Serialize> Serialize for Complex<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(NAME, &(&self.re, &self.im))
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Complex<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_newtype_struct(NAME, ComplexVisitor(PhantomData))
+    }
+}
+
+struct ComplexVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for ComplexVisitor<T> {
+    type Value = Complex<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a complex number")
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_tuple(2, self)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let re = seq
+            .next_element()?
+            .ok_or_else(|| A::Error::invalid_length(0, &self))?;
+        let im = seq
+            .next_element()?
+            .ok_or_else(|| A::Error::invalid_length(1, &self))?;
+        Ok(Complex { re, im })
+    }
+}
+
+#[cfg(feature = "num-complex")]
+impl<T> From<::num_complex::Complex<T>> for Complex<T> {
+    fn from(value: ::num_complex::Complex<T>) -> Self {
+        Self::new(value.re, value.im)
+    }
+}
+
+#[cfg(feature = "num-complex")]
+impl<T> From<Complex<T>> for ::num_complex::Complex<T> {
+    fn from(value: Complex<T>) -> Self {
+        Self::new(value.re, value.im)
+    }
+}
+
+/// Serializes [`num_complex::Complex`](::num_complex::Complex)s like [`Complex`]es, for use with `#[serde(with)]`.
+///
+/// ```ignore
+/// # use serde::{Deserialize, Serialize};
+/// #[derive(Serialize, Deserialize)]
+/// struct Signal {
+///     #[serde(with = "serde_beve::num_complex")]
+///     peak: num_complex::Complex<f64>,
+///     #[serde(with = "serde_beve::num_complex::vec")]
+///     samples: Vec<num_complex::Complex<f64>>,
+/// }
+/// ```
+#[cfg(feature = "num-complex")]
+pub mod num_complex {
+    use super::Complex;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<T, S>(
+        value: &::num_complex::Complex<T>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        Complex::new(&value.re, &value.im).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<::num_complex::Complex<T>, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        Complex::deserialize(deserializer).map(Into::into)
+    }
+
+    /// Serializes [`Vec`]s of [`num_complex::Complex`](::num_complex::Complex)s as complex arrays.
+    pub mod vec {
+        use super::Complex;
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<T, S>(
+            value: &[::num_complex::Complex<T>],
+            serializer: S,
+        ) -> Result<S::Ok, S::Error>
+        where
+            T: serde::Serialize,
+            S: Serializer,
+        {
+            serializer.collect_seq(value.iter().map(|v| Complex::new(&v.re, &v.im)))
+        }
+
+        pub fn deserialize<'de, T, D>(
+            deserializer: D,
+        ) -> Result<Vec<::num_complex::Complex<T>>, D::Error>
+        where
+            T: Deserialize<'de>,
+            D: Deserializer<'de>,
+        {
+            Vec::<Complex<T>>::deserialize(deserializer)
+                .map(|v| v.into_iter().map(Into::into).collect())
+        }
+    }
+}
@@ -1,26 +1,92 @@
+mod bits;
 mod complex;
 mod enums;
 mod map;
 mod matrix;
 mod seq;
 
-use crate::{Error, error::SpecialType, headers::*};
+use crate::{Error, Number, error::Violation, headers::*, matrix::MatrixLayout};
+use bits::BoolArrayDeserializer;
 use complex::{ComplexArrayDeserializer, ComplexDeserializer, ComplexKind};
-use enums::EnumDeserializer;
+use enums::{EnumDeserializer, Variant};
 use map::MapDeserializer;
 use matrix::MatrixDeserializer;
 use seq::SeqDeserializer;
+#[cfg(feature = "half")]
+use serde::de::IntoDeserializer;
 use serde::{de::Visitor, forward_to_deserialize_any};
 use std::io::Read;
 
 pub struct Deserializer<R: Read> {
     reader: R,
     peek: Option<u8>,
+    strict: bool,
+    human_readable: bool,
+    lsb_first_bools: bool,
 }
 
 impl<R: Read> Deserializer<R> {
     pub fn new(reader: R) -> Self {
-        Self { reader, peek: None }
+        Self {
+            reader,
+            peek: None,
+            strict: false,
+            human_readable: false,
+            lsb_first_bools: false,
+        }
+    }
+
+    /// Report the format as human-readable to [`Deserialize`](serde::Deserialize)
+    /// implementations.
+    ///
+    /// BEVE is a binary format, so types with both a textual and a compact representation (e.g.
+    /// IP addresses) are expected in their compact form by default. Enable this to read data
+    /// written with [`Serializer::human_readable`](crate::Serializer::human_readable) enabled.
+    pub fn human_readable(mut self, human_readable: bool) -> Self {
+        self.human_readable = human_readable;
+        self
+    }
+
+    /// Reject any input that is not in canonical form.
+    ///
+    /// In strict mode, deserialization fails with [`Error::NonCanonical`] if a size is written
+    /// with more bytes than necessary, the keys of an object are unsorted or duplicated, a float
+    /// (of any width) is a non-canonical NaN or a negative zero, or the unused bits of the last
+    /// byte of a boolean array are set. Input that passes is exactly what
+    /// [`Serializer::canonical`](crate::Serializer::canonical) would have written for the same
+    /// data.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Read boolean arrays packed from the least significant bit of each byte.
+    ///
+    /// Versions of this crate before 2.0.0 packed boolean arrays in that order, unlike the
+    /// reference implementation. Enable this to read data they wrote, and write it back out with a
+    /// current [`Serializer`](crate::Serializer) to migrate it.
+    pub fn lsb_first_bool_arrays(mut self, lsb_first: bool) -> Self {
+        self.lsb_first_bools = lsb_first;
+        self
+    }
+
+    /// Brings a byte of a boolean array into the order it is written in by the reference
+    /// implementation.
+    pub(self) fn bool_array_byte(&self, byte: u8) -> u8 {
+        if self.lsb_first_bools {
+            byte.reverse_bits()
+        } else {
+            byte
+        }
+    }
+
+    /// Checks the `last` byte of a boolean array of `len` booleans, which must have its unused bits
+    /// unset in strict mode.
+    pub(self) fn check_bool_array_padding(&self, len: usize, last: u8) -> Result<(), Error> {
+        if self.strict && len % 8 != 0 && last & (0xff >> (len % 8)) != 0 {
+            return Err(Error::NonCanonical(Violation::BoolArrayPadding));
+        }
+        Ok(())
     }
 
     fn read_byte(&mut self) -> Result<u8, Error> {
@@ -479,6 +545,26 @@ impl<R: Read> Deserializer<R> {
         visitor.visit_seq(SeqDeserializer::new(self, size, ArrayKind::String))
     }
 
+    fn get_object_kind(&mut self) -> Result<ObjectKind, Error> {
+        match self.get_byte()? {
+            STRING_OBJECT => Ok(ObjectKind::String),
+            I8_OBJECT => Ok(ObjectKind::I8),
+            I16_OBJECT => Ok(ObjectKind::I16),
+            I32_OBJECT => Ok(ObjectKind::I32),
+            I64_OBJECT => Ok(ObjectKind::I64),
+            I128_OBJECT => Ok(ObjectKind::I128),
+            U8_OBJECT => Ok(ObjectKind::U8),
+            U16_OBJECT => Ok(ObjectKind::U16),
+            U32_OBJECT => Ok(ObjectKind::U32),
+            U64_OBJECT => Ok(ObjectKind::U64),
+            U128_OBJECT => Ok(ObjectKind::U128),
+            header => Err(Error::WrongType {
+                expected: "object",
+                found: header_name(header),
+            }),
+        }
+    }
+
     pub(self) fn get_size(&mut self) -> Result<usize, Error> {
         let first = self.get_byte()?;
         let n_bytes = 2_usize.pow((first & 0b11) as u32);
@@ -506,7 +592,21 @@ impl<R: Read> Deserializer<R> {
             bytes[i + 1] = byte;
         }
 
-        Ok(usize::from_le_bytes(bytes) >> 2)
+        let size = usize::from_le_bytes(bytes) >> 2;
+        let minimal = if size < 2_usize.pow(6) {
+            1
+        } else if size < 2_usize.pow(14) {
+            2
+        } else if size < 2_usize.pow(30) {
+            4
+        } else {
+            8
+        };
+        if self.strict && n_bytes > minimal {
+            return Err(Error::NonCanonical(Violation::OversizedSize));
+        }
+
+        Ok(size)
     }
 
     pub(self) fn get_string_value(&mut self) -> Result<String, Error> {
@@ -562,36 +662,208 @@ impl<R: Read> Deserializer<R> {
         self.get_num_value(i128::from_le_bytes)
     }
 
+    /// Reads the bits of a brain float, which must be canonical in strict mode.
+    #[cfg(feature = "half")]
+    pub(self) fn get_bf16_bits(&mut self) -> Result<u16, Error> {
+        let bits = self.get_num_value(u16::from_le_bytes)?;
+        if self.strict && !is_canonical_bf16(bits) {
+            return Err(Error::NonCanonical(Violation::NonCanonicalFloat));
+        }
+        Ok(bits)
+    }
+
+    /// Reads the bits of an `f16`, which must be canonical in strict mode.
+    #[cfg(feature = "half")]
+    pub(self) fn get_f16_bits(&mut self) -> Result<u16, Error> {
+        let bits = self.get_num_value(u16::from_le_bytes)?;
+        if self.strict && !is_canonical_f16(bits) {
+            return Err(Error::NonCanonical(Violation::NonCanonicalFloat));
+        }
+        Ok(bits)
+    }
+
     pub(self) fn get_bf16_value(&mut self) -> Result<f32, Error> {
         #[cfg(feature = "half")]
         {
-            self.get_num_value(half::bf16::from_le_bytes)
-                .map(|v| v.to_f32())
+            self.get_bf16_bits()
+                .map(|bits| half::bf16::from_bits(bits).to_f32())
         }
         #[cfg(not(feature = "half"))]
         {
-            Err(Error::UnsupportedDataType(SpecialType::BrainFloat))
+            Err(Error::UnsupportedDataType(
+                crate::error::SpecialType::BrainFloat,
+            ))
         }
     }
 
     pub(self) fn get_f16_value(&mut self) -> Result<f32, Error> {
         #[cfg(feature = "half")]
         {
-            self.get_num_value(half::f16::from_le_bytes)
-                .map(|v| v.to_f32())
+            self.get_f16_bits()
+                .map(|bits| half::f16::from_bits(bits).to_f32())
         }
         #[cfg(not(feature = "half"))]
         {
-            Err(Error::UnsupportedDataType(SpecialType::HalfFloat))
+            Err(Error::UnsupportedDataType(
+                crate::error::SpecialType::HalfFloat,
+            ))
         }
     }
 
+    pub(self) fn get_f128_value(&mut self) -> Result<[u8; 16], Error> {
+        self.get_num_value(std::convert::identity)
+    }
+
     pub(self) fn get_f32_value(&mut self) -> Result<f32, Error> {
-        self.get_num_value(f32::from_le_bytes)
+        let v = self.get_num_value(f32::from_le_bytes)?;
+        if self.strict && !is_canonical_f32(v) {
+            return Err(Error::NonCanonical(Violation::NonCanonicalFloat));
+        }
+        Ok(v)
     }
 
     pub(self) fn get_f64_value(&mut self) -> Result<f64, Error> {
-        self.get_num_value(f64::from_le_bytes)
+        let v = self.get_num_value(f64::from_le_bytes)?;
+        if self.strict && !is_canonical_f64(v) {
+            return Err(Error::NonCanonical(Violation::NonCanonicalFloat));
+        }
+        Ok(v)
+    }
+
+    /// Reads the elements of a numeric array of `kind` all at once, and gives them to the visitor of
+    /// a [`TypedArray`](crate::TypedArray).
+    fn deserialize_typed_array<'de, V: Visitor<'de>>(
+        &mut self,
+        kind: ArrayKind,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let header = self.get_byte()?;
+        if header != kind.array_header() {
+            return self.deserialize_widened_array(kind, header, visitor);
+        }
+
+        let len = self.get_size()?;
+        match kind {
+            ArrayKind::I8 => self.read_typed_array::<i8, _>(len, visitor),
+            ArrayKind::I16 => self.read_typed_array::<i16, _>(len, visitor),
+            ArrayKind::I32 => self.read_typed_array::<i32, _>(len, visitor),
+            ArrayKind::I64 => self.read_typed_array::<i64, _>(len, visitor),
+            ArrayKind::I128 => self.read_typed_array::<i128, _>(len, visitor),
+            ArrayKind::U8 => self.read_typed_array::<u8, _>(len, visitor),
+            ArrayKind::U16 => self.read_typed_array::<u16, _>(len, visitor),
+            ArrayKind::U32 => self.read_typed_array::<u32, _>(len, visitor),
+            ArrayKind::U64 => self.read_typed_array::<u64, _>(len, visitor),
+            ArrayKind::U128 => self.read_typed_array::<u128, _>(len, visitor),
+            #[cfg(feature = "half")]
+            ArrayKind::BF16 => {
+                let elements = self.read_elements::<half::bf16>(len)?;
+                if self.strict && !elements.iter().all(|v| is_canonical_bf16(v.to_bits())) {
+                    return Err(Error::NonCanonical(Violation::NonCanonicalFloat));
+                }
+                crate::typed::visit_elements(elements, visitor)
+            }
+            #[cfg(feature = "half")]
+            ArrayKind::F16 => {
+                let elements = self.read_elements::<half::f16>(len)?;
+                if self.strict && !elements.iter().all(|v| is_canonical_f16(v.to_bits())) {
+                    return Err(Error::NonCanonical(Violation::NonCanonicalFloat));
+                }
+                crate::typed::visit_elements(elements, visitor)
+            }
+            ArrayKind::F32 => {
+                let elements = self.read_elements::<f32>(len)?;
+                if self.strict && !elements.iter().all(|v| is_canonical_f32(*v)) {
+                    return Err(Error::NonCanonical(Violation::NonCanonicalFloat));
+                }
+                crate::typed::visit_elements(elements, visitor)
+            }
+            ArrayKind::F64 => {
+                let elements = self.read_elements::<f64>(len)?;
+                if self.strict && !elements.iter().all(|v| is_canonical_f64(*v)) {
+                    return Err(Error::NonCanonical(Violation::NonCanonicalFloat));
+                }
+                crate::typed::visit_elements(elements, visitor)
+            }
+            // Only numbers have typed arrays
+            _ => unreachable!(),
+        }
+    }
+
+    /// Reads an array of narrower numbers of the same kind as `kind`, such as those written by
+    /// [`Serializer::compact_numbers`](crate::Serializer::compact_numbers), into a typed array of
+    /// `kind`.
+    fn deserialize_widened_array<'de, V: Visitor<'de>>(
+        &mut self,
+        kind: ArrayKind,
+        header: u8,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match (kind, header) {
+            (ArrayKind::I16, I8_ARRAY) => self.read_widened_array::<i8, i16, _>(visitor),
+            (ArrayKind::I32, I8_ARRAY) => self.read_widened_array::<i8, i32, _>(visitor),
+            (ArrayKind::I32, I16_ARRAY) => self.read_widened_array::<i16, i32, _>(visitor),
+            (ArrayKind::I64, I8_ARRAY) => self.read_widened_array::<i8, i64, _>(visitor),
+            (ArrayKind::I64, I16_ARRAY) => self.read_widened_array::<i16, i64, _>(visitor),
+            (ArrayKind::I64, I32_ARRAY) => self.read_widened_array::<i32, i64, _>(visitor),
+            (ArrayKind::I128, I8_ARRAY) => self.read_widened_array::<i8, i128, _>(visitor),
+            (ArrayKind::I128, I16_ARRAY) => self.read_widened_array::<i16, i128, _>(visitor),
+            (ArrayKind::I128, I32_ARRAY) => self.read_widened_array::<i32, i128, _>(visitor),
+            (ArrayKind::I128, I64_ARRAY) => self.read_widened_array::<i64, i128, _>(visitor),
+            (ArrayKind::U16, U8_ARRAY) => self.read_widened_array::<u8, u16, _>(visitor),
+            (ArrayKind::U32, U8_ARRAY) => self.read_widened_array::<u8, u32, _>(visitor),
+            (ArrayKind::U32, U16_ARRAY) => self.read_widened_array::<u16, u32, _>(visitor),
+            (ArrayKind::U64, U8_ARRAY) => self.read_widened_array::<u8, u64, _>(visitor),
+            (ArrayKind::U64, U16_ARRAY) => self.read_widened_array::<u16, u64, _>(visitor),
+            (ArrayKind::U64, U32_ARRAY) => self.read_widened_array::<u32, u64, _>(visitor),
+            (ArrayKind::U128, U8_ARRAY) => self.read_widened_array::<u8, u128, _>(visitor),
+            (ArrayKind::U128, U16_ARRAY) => self.read_widened_array::<u16, u128, _>(visitor),
+            (ArrayKind::U128, U32_ARRAY) => self.read_widened_array::<u32, u128, _>(visitor),
+            (ArrayKind::U128, U64_ARRAY) => self.read_widened_array::<u64, u128, _>(visitor),
+            (ArrayKind::F64, F32_ARRAY) => {
+                let len = self.get_size()?;
+                let elements = self.read_elements::<f32>(len)?;
+                if self.strict && !elements.iter().all(|v| is_canonical_f32(*v)) {
+                    return Err(Error::NonCanonical(Violation::NonCanonicalFloat));
+                }
+                let elements: Vec<f64> = elements.into_iter().map(f64::from).collect();
+                crate::typed::visit_elements(elements, visitor)
+            }
+            _ => Err(Error::WrongType {
+                expected: header_name(kind.array_header()),
+                found: header_name(header),
+            }),
+        }
+    }
+
+    fn read_widened_array<'de, N: Number, T: Number + From<N>, V: Visitor<'de>>(
+        &mut self,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let len = self.get_size()?;
+        let elements: Vec<T> = self
+            .read_elements::<N>(len)?
+            .into_iter()
+            .map(T::from)
+            .collect();
+        crate::typed::visit_elements(elements, visitor)
+    }
+
+    fn read_typed_array<'de, T: Number, V: Visitor<'de>>(
+        &mut self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let elements = self.read_elements::<T>(len)?;
+        crate::typed::visit_elements(elements, visitor)
+    }
+
+    /// Reads `len` numbers directly into the memory of a `Vec`.
+    fn read_elements<T: Number>(&mut self, len: usize) -> Result<Vec<T>, Error> {
+        len.checked_mul(size_of::<T>()).ok_or(Error::TooLong)?;
+        let mut elements = vec![T::default(); len];
+        self.reader
+            .read_exact(bytemuck::cast_slice_mut(&mut elements))?;
+        Ok(elements)
     }
 
     fn deserialize_complex<'de, V: Visitor<'de>>(&mut self, visitor: V) -> Result<V::Value, Error> {
@@ -607,7 +879,7 @@ impl<R: Read> Deserializer<R> {
 
         let complex_header = self.get_byte()?;
         let array = complex_header & 1 == 1;
-        let kind = match complex_header {
+        let kind = match complex_header & NUM_TYPE_MASK {
             I8_HEADER => ComplexKind::I8,
             I16_HEADER => ComplexKind::I16,
             I32_HEADER => ComplexKind::I32,
@@ -646,16 +918,43 @@ impl<R: Read> Deserializer<R> {
             }
         }
 
-        let layout = if self.get_byte()? & 1 == 1 {
-            "layout_right"
-        } else {
-            "layout_left"
-        };
-
-        visitor.visit_map(MatrixDeserializer::new(self, layout.to_string()))
+        let layout = MatrixLayout::from_bit(self.get_byte()?);
+        visitor.visit_map(MatrixDeserializer::new(self, layout))
     }
 }
 
+/// Whether a float is neither a NaN other than the canonical quiet NaN, nor a negative zero.
+fn is_canonical_f32(v: f32) -> bool {
+    !((v.is_nan() && v.to_bits() != f32::NAN.to_bits()) || (v == 0.0 && v.is_sign_negative()))
+}
+
+/// Whether a float is neither a NaN other than the canonical quiet NaN, nor a negative zero.
+fn is_canonical_f64(v: f64) -> bool {
+    !((v.is_nan() && v.to_bits() != f64::NAN.to_bits()) || (v == 0.0 && v.is_sign_negative()))
+}
+
+/// Whether the bits of an `f16` are neither a NaN other than the canonical quiet NaN, nor a
+/// negative zero.
+#[cfg(feature = "half")]
+fn is_canonical_f16(bits: u16) -> bool {
+    is_canonical_half(bits, 0x7c00, half::f16::NAN.to_bits())
+}
+
+/// Whether the bits of a brain float are neither a NaN other than the canonical quiet NaN, nor a
+/// negative zero.
+#[cfg(feature = "half")]
+fn is_canonical_bf16(bits: u16) -> bool {
+    is_canonical_half(bits, 0x7f80, half::bf16::NAN.to_bits())
+}
+
+/// Whether the bits of a 16-bit float, whose infinity has the bits `infinity`, are neither a NaN
+/// other than `nan`, nor a negative zero.
+#[cfg(feature = "half")]
+fn is_canonical_half(bits: u16, infinity: u16, nan: u16) -> bool {
+    let is_nan = bits & 0x7fff > infinity;
+    !((is_nan && bits != nan) || bits == 0x8000)
+}
+
 impl<'de, R: Read> serde::Deserializer<'de> for &mut Deserializer<R> {
     type Error = Error;
 
@@ -671,7 +970,10 @@ impl<'de, R: Read> serde::Deserializer<'de> for &mut Deserializer<R> {
             F16 => self.deserialize_f16(visitor),
             F32 => self.deserialize_f32(visitor),
             F64 => self.deserialize_f64(visitor),
-            F128 => Err(Error::UnsupportedDataType(SpecialType::F128)),
+            F128 => {
+                self.get_byte()?;
+                visitor.visit_bytes(&self.get_f128_value()?)
+            }
 
             I8 => self.deserialize_i8(visitor),
             I16 => self.deserialize_i16(visitor),
@@ -705,7 +1007,11 @@ impl<'de, R: Read> serde::Deserializer<'de> for &mut Deserializer<R> {
             F16_ARRAY => self.deserialize_f16_array(visitor),
             F32_ARRAY => self.deserialize_f32_array(visitor),
             F64_ARRAY => self.deserialize_f64_array(visitor),
-            F128_ARRAY => Err(Error::UnsupportedDataType(SpecialType::F128)),
+            F128_ARRAY => {
+                self.get_byte()?;
+                let size = self.get_size()?;
+                visitor.visit_seq(SeqDeserializer::new(self, size, ArrayKind::F128))
+            }
 
             I8_ARRAY => self.deserialize_i8_array(visitor),
             I16_ARRAY => self.deserialize_i16_array(visitor),
@@ -769,6 +1075,7 @@ impl<'de, R: Read> serde::Deserializer<'de> for &mut Deserializer<R> {
     {
         match self.get_byte()? {
             I16 => visitor.visit_i16(self.get_i16_value()?),
+            I8 => visitor.visit_i8(self.get_i8_value()?),
             header => Err(Error::WrongType {
                 expected: header_name(I16),
                 found: header_name(header),
@@ -782,6 +1089,8 @@ impl<'de, R: Read> serde::Deserializer<'de> for &mut Deserializer<R> {
     {
         match self.get_byte()? {
             I32 => visitor.visit_i32(self.get_i32_value()?),
+            I8 => visitor.visit_i8(self.get_i8_value()?),
+            I16 => visitor.visit_i16(self.get_i16_value()?),
             header => Err(Error::WrongType {
                 expected: header_name(I32),
                 found: header_name(header),
@@ -795,6 +1104,9 @@ impl<'de, R: Read> serde::Deserializer<'de> for &mut Deserializer<R> {
     {
         match self.get_byte()? {
             I64 => visitor.visit_i64(self.get_i64_value()?),
+            I8 => visitor.visit_i8(self.get_i8_value()?),
+            I16 => visitor.visit_i16(self.get_i16_value()?),
+            I32 => visitor.visit_i32(self.get_i32_value()?),
             header => Err(Error::WrongType {
                 expected: header_name(I64),
                 found: header_name(header),
@@ -808,6 +1120,10 @@ impl<'de, R: Read> serde::Deserializer<'de> for &mut Deserializer<R> {
     {
         match self.get_byte()? {
             I128 => visitor.visit_i128(self.get_i128_value()?),
+            I8 => visitor.visit_i8(self.get_i8_value()?),
+            I16 => visitor.visit_i16(self.get_i16_value()?),
+            I32 => visitor.visit_i32(self.get_i32_value()?),
+            I64 => visitor.visit_i64(self.get_i64_value()?),
             header => Err(Error::WrongType {
                 expected: header_name(I128),
                 found: header_name(header),
@@ -834,6 +1150,7 @@ impl<'de, R: Read> serde::Deserializer<'de> for &mut Deserializer<R> {
     {
         match self.get_byte()? {
             U16 => visitor.visit_u16(self.get_u16_value()?),
+            U8 => visitor.visit_u8(self.get_u8_value()?),
             header => Err(Error::WrongType {
                 expected: header_name(U16),
                 found: header_name(header),
@@ -847,6 +1164,8 @@ impl<'de, R: Read> serde::Deserializer<'de> for &mut Deserializer<R> {
     {
         match self.get_byte()? {
             U32 => visitor.visit_u32(self.get_u32_value()?),
+            U8 => visitor.visit_u8(self.get_u8_value()?),
+            U16 => visitor.visit_u16(self.get_u16_value()?),
             header => Err(Error::WrongType {
                 expected: header_name(U32),
                 found: header_name(header),
@@ -860,6 +1179,9 @@ impl<'de, R: Read> serde::Deserializer<'de> for &mut Deserializer<R> {
     {
         match self.get_byte()? {
             U64 => visitor.visit_u64(self.get_u64_value()?),
+            U8 => visitor.visit_u8(self.get_u8_value()?),
+            U16 => visitor.visit_u16(self.get_u16_value()?),
+            U32 => visitor.visit_u32(self.get_u32_value()?),
             header => Err(Error::WrongType {
                 expected: header_name(U64),
                 found: header_name(header),
@@ -873,6 +1195,10 @@ impl<'de, R: Read> serde::Deserializer<'de> for &mut Deserializer<R> {
     {
         match self.get_byte()? {
             U128 => visitor.visit_u128(self.get_u128_value()?),
+            U8 => visitor.visit_u8(self.get_u8_value()?),
+            U16 => visitor.visit_u16(self.get_u16_value()?),
+            U32 => visitor.visit_u32(self.get_u32_value()?),
+            U64 => visitor.visit_u64(self.get_u64_value()?),
             header => Err(Error::WrongType {
                 expected: header_name(U128),
                 found: header_name(header),
@@ -886,6 +1212,8 @@ impl<'de, R: Read> serde::Deserializer<'de> for &mut Deserializer<R> {
     {
         match self.get_byte()? {
             F32 => visitor.visit_f32(self.get_f32_value()?),
+            BF16 => visitor.visit_f32(self.get_bf16_value()?),
+            F16 => visitor.visit_f32(self.get_f16_value()?),
             header => Err(Error::WrongType {
                 expected: header_name(F32),
                 found: header_name(header),
@@ -899,6 +1227,9 @@ impl<'de, R: Read> serde::Deserializer<'de> for &mut Deserializer<R> {
     {
         match self.get_byte()? {
             F64 => visitor.visit_f64(self.get_f64_value()?),
+            F32 => visitor.visit_f32(self.get_f32_value()?),
+            BF16 => visitor.visit_f32(self.get_bf16_value()?),
+            F16 => visitor.visit_f32(self.get_f16_value()?),
             header => Err(Error::WrongType {
                 expected: header_name(F64),
                 found: header_name(header),
@@ -923,6 +1254,10 @@ impl<'de, R: Read> serde::Deserializer<'de> for &mut Deserializer<R> {
     where
         V: Visitor<'de>,
     {
+        if self.peek_byte()? == COMPLEX {
+            return self.deserialize_complex(visitor);
+        }
+
         let kind = match self.get_byte()? {
             STRING_ARRAY => ArrayKind::String,
             BOOL_ARRAY => ArrayKind::Boolean,
@@ -938,6 +1273,9 @@ impl<'de, R: Read> serde::Deserializer<'de> for &mut Deserializer<R> {
             U128_ARRAY => ArrayKind::U128,
             F32_ARRAY => ArrayKind::F32,
             F64_ARRAY => ArrayKind::F64,
+            F128_ARRAY => ArrayKind::F128,
+            BF16_ARRAY => ArrayKind::BF16,
+            F16_ARRAY => ArrayKind::F16,
             GENERIC_ARRAY => ArrayKind::Generic,
             header => {
                 return Err(Error::WrongType {
@@ -954,26 +1292,11 @@ impl<'de, R: Read> serde::Deserializer<'de> for &mut Deserializer<R> {
     where
         V: Visitor<'de>,
     {
-        let kind = match self.get_byte()? {
-            STRING_OBJECT => ObjectKind::String,
-            I8_OBJECT => ObjectKind::I8,
-            I16_OBJECT => ObjectKind::I16,
-            I32_OBJECT => ObjectKind::I32,
-            I64_OBJECT => ObjectKind::I64,
-            I128_OBJECT => ObjectKind::I128,
-            U8_OBJECT => ObjectKind::U8,
-            U16_OBJECT => ObjectKind::U16,
-            U32_OBJECT => ObjectKind::U32,
-            U64_OBJECT => ObjectKind::U64,
-            U128_OBJECT => ObjectKind::U128,
-            header => {
-                return Err(Error::WrongType {
-                    expected: "object",
-                    found: header_name(header),
-                });
-            }
-        };
+        if self.peek_byte()? == MATRIX {
+            return self.deserialize_matrix(visitor);
+        }
 
+        let kind = self.get_object_kind()?;
         let size = self.get_size()?;
         visitor.visit_map(MapDeserializer::new(self, size, kind))
     }
@@ -987,17 +1310,94 @@ impl<'de, R: Read> serde::Deserializer<'de> for &mut Deserializer<R> {
     where
         V: Visitor<'de>,
     {
-        visitor.visit_enum(EnumDeserializer { deserializer: self })
+        match self.get_byte()? {
+            TAG => visitor.visit_enum(EnumDeserializer {
+                deserializer: self,
+                variant: Variant::Index,
+            }),
+            // Written by `Serializer::variants_by_name`
+            STRING => {
+                let name = self.get_string_value()?;
+                visitor.visit_enum(EnumDeserializer {
+                    deserializer: self,
+                    variant: Variant::Unit(name),
+                })
+            }
+            STRING_OBJECT => {
+                let size = self.get_size()?;
+                if size != 1 {
+                    return Err(Error::WrongType {
+                        expected: "single-field object",
+                        found: header_name(STRING_OBJECT),
+                    });
+                }
+                let name = self.get_string_value()?;
+                visitor.visit_enum(EnumDeserializer {
+                    deserializer: self,
+                    variant: Variant::Named(name),
+                })
+            }
+            header => Err(Error::WrongType {
+                expected: header_name(TAG),
+                found: header_name(header),
+            }),
+        }
     }
 
     fn deserialize_newtype_struct<V>(
         self,
-        _name: &'static str,
+        name: &'static str,
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
+        if name == crate::f128::NAME && self.peek_byte()? == F128 {
+            self.get_byte()?;
+            return visitor.visit_bytes(&self.get_f128_value()?);
+        }
+        if name == crate::matrix::NAME && self.peek_byte()? == MATRIX {
+            self.get_byte()?;
+            let layout = MatrixLayout::from_bit(self.get_byte()?);
+            return visitor.visit_seq(MatrixDeserializer::new(self, layout));
+        }
+        if let Some(kind) = crate::typed::kind(name) {
+            return self.deserialize_typed_array(kind, visitor);
+        }
+        if name == crate::bits::NAME && self.peek_byte()? == BOOL_ARRAY {
+            self.get_byte()?;
+            let len = self.get_size()?;
+            let mut bytes = vec![0; len.div_ceil(8)];
+            self.reader.read_exact(&mut bytes)?;
+            for byte in &mut bytes {
+                *byte = self.bool_array_byte(*byte);
+            }
+            if let Some(last) = bytes.last() {
+                self.check_bool_array_padding(len, *last)?;
+            }
+            return visitor.visit_seq(BoolArrayDeserializer::new(len, bytes));
+        }
+        if name == crate::complex::NAME && self.peek_byte()? == COMPLEX {
+            return self.deserialize_complex(visitor);
+        }
+        // `half::f16` and `half::bf16` deserialize from newtype structs of their bits
+        #[cfg(feature = "half")]
+        match (name, self.peek_byte()?) {
+            ("f16", F16) | ("bf16", BF16) => {
+                self.get_byte()?;
+                let bits = if name == "f16" {
+                    self.get_f16_bits()?
+                } else {
+                    self.get_bf16_bits()?
+                };
+                return visitor.visit_newtype_struct(bits.into_deserializer());
+            }
+            ("f16" | "bf16", F16 | BF16 | F32 | F64) => return self.deserialize_any(visitor),
+            _ => {}
+        }
+        #[cfg(not(feature = "half"))]
+        let _ = name;
+
         visitor.visit_newtype_struct(self)
     }
 
@@ -1087,8 +1487,33 @@ impl<'de, R: Read> serde::Deserializer<'de> for &mut Deserializer<R> {
         }
     }
 
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.peek_byte()? {
+            // Written by `Serializer::structs_as_arrays`, with fields in declaration order
+            GENERIC_ARRAY => self.deserialize_seq(visitor),
+            MATRIX => self.deserialize_matrix(visitor),
+            _ => {
+                let kind = self.get_object_kind()?;
+                let size = self.get_size()?;
+                visitor.visit_map(MapDeserializer::new_struct(self, size, kind, fields))
+            }
+        }
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.human_readable
+    }
+
     forward_to_deserialize_any! {
-        unit_struct tuple tuple_struct struct identifier ignored_any
+        unit_struct tuple tuple_struct identifier ignored_any
     }
 }
 
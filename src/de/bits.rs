@@ -0,0 +1,41 @@
+use crate::Error;
+use serde::de::{DeserializeSeed, IntoDeserializer, SeqAccess, value::BytesDeserializer};
+
+/// Reads the length and packed bytes of a boolean array as a sequence, as
+/// [`bitvec`](crate::bits) reads them.
+pub struct BoolArrayDeserializer {
+    len: usize,
+    bytes: Vec<u8>,
+    index: usize,
+}
+
+impl BoolArrayDeserializer {
+    pub fn new(len: usize, bytes: Vec<u8>) -> Self {
+        Self {
+            len,
+            bytes,
+            index: 0,
+        }
+    }
+}
+
+impl<'de> SeqAccess<'de> for BoolArrayDeserializer {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let out = match self.index {
+            0 => seed.deserialize((self.len as u64).into_deserializer()),
+            1 => seed.deserialize(BytesDeserializer::new(&self.bytes)),
+            _ => return Ok(None),
+        };
+        self.index += 1;
+        out.map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(2 - self.index)
+    }
+}
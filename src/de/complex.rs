@@ -56,8 +56,21 @@ impl<'a, R: Read> ComplexDeserializer<'a, R> {
         }
     }
 
+    /// Ensures that parts of type `expected` can be read, which they can if they are of that type
+    /// or a narrower one of the same kind.
     fn ensure_kind(&mut self, expected: ComplexKind) -> Result<(), Error> {
-        if self.kind == expected {
+        let widens = [SIGNED.as_slice(), &UNSIGNED, &FLOAT]
+            .into_iter()
+            .any(|family| {
+                match (
+                    family.iter().position(|k| *k == self.kind),
+                    family.iter().position(|k| *k == expected),
+                ) {
+                    (Some(found), Some(expected)) => found <= expected,
+                    _ => false,
+                }
+            });
+        if widens {
             Ok(())
         } else {
             Err(Error::MismatchedElementType {
@@ -66,8 +79,44 @@ impl<'a, R: Read> ComplexDeserializer<'a, R> {
             })
         }
     }
+
+    fn visit_part<'de, V>(&mut self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        match self.kind {
+            ComplexKind::I8 => visitor.visit_i8(self.deserializer.get_i8_value()?),
+            ComplexKind::I16 => visitor.visit_i16(self.deserializer.get_i16_value()?),
+            ComplexKind::I32 => visitor.visit_i32(self.deserializer.get_i32_value()?),
+            ComplexKind::I64 => visitor.visit_i64(self.deserializer.get_i64_value()?),
+            ComplexKind::I128 => visitor.visit_i128(self.deserializer.get_i128_value()?),
+            ComplexKind::U8 => visitor.visit_u8(self.deserializer.get_u8_value()?),
+            ComplexKind::U16 => visitor.visit_u16(self.deserializer.get_u16_value()?),
+            ComplexKind::U32 => visitor.visit_u32(self.deserializer.get_u32_value()?),
+            ComplexKind::U64 => visitor.visit_u64(self.deserializer.get_u64_value()?),
+            ComplexKind::U128 => visitor.visit_u128(self.deserializer.get_u128_value()?),
+            ComplexKind::F32 => visitor.visit_f32(self.deserializer.get_f32_value()?),
+            ComplexKind::F64 => visitor.visit_f64(self.deserializer.get_f64_value()?),
+        }
+    }
 }
 
+const SIGNED: [ComplexKind; 5] = [
+    ComplexKind::I8,
+    ComplexKind::I16,
+    ComplexKind::I32,
+    ComplexKind::I64,
+    ComplexKind::I128,
+];
+const UNSIGNED: [ComplexKind; 5] = [
+    ComplexKind::U8,
+    ComplexKind::U16,
+    ComplexKind::U32,
+    ComplexKind::U64,
+    ComplexKind::U128,
+];
+const FLOAT: [ComplexKind; 2] = [ComplexKind::F32, ComplexKind::F64];
+
 impl<'a, 'de, R: Read> SeqAccess<'de> for ComplexDeserializer<'a, R> {
     type Error = Error;
 
@@ -106,7 +155,7 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut ComplexDeserializer<'a,
         V: serde::de::Visitor<'de>,
     {
         self.ensure_kind(ComplexKind::I8)?;
-        visitor.visit_i8(self.deserializer.get_i8_value()?)
+        self.visit_part(visitor)
     }
 
     fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -114,7 +163,7 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut ComplexDeserializer<'a,
         V: serde::de::Visitor<'de>,
     {
         self.ensure_kind(ComplexKind::I16)?;
-        visitor.visit_i16(self.deserializer.get_i16_value()?)
+        self.visit_part(visitor)
     }
 
     fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -122,7 +171,7 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut ComplexDeserializer<'a,
         V: serde::de::Visitor<'de>,
     {
         self.ensure_kind(ComplexKind::I32)?;
-        visitor.visit_i32(self.deserializer.get_i32_value()?)
+        self.visit_part(visitor)
     }
 
     fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -130,7 +179,7 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut ComplexDeserializer<'a,
         V: serde::de::Visitor<'de>,
     {
         self.ensure_kind(ComplexKind::I64)?;
-        visitor.visit_i64(self.deserializer.get_i64_value()?)
+        self.visit_part(visitor)
     }
 
     fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -138,7 +187,7 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut ComplexDeserializer<'a,
         V: serde::de::Visitor<'de>,
     {
         self.ensure_kind(ComplexKind::I128)?;
-        visitor.visit_i128(self.deserializer.get_i128_value()?)
+        self.visit_part(visitor)
     }
 
     fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -146,7 +195,7 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut ComplexDeserializer<'a,
         V: serde::de::Visitor<'de>,
     {
         self.ensure_kind(ComplexKind::U8)?;
-        visitor.visit_u8(self.deserializer.get_u8_value()?)
+        self.visit_part(visitor)
     }
 
     fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -154,7 +203,7 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut ComplexDeserializer<'a,
         V: serde::de::Visitor<'de>,
     {
         self.ensure_kind(ComplexKind::U16)?;
-        visitor.visit_u16(self.deserializer.get_u16_value()?)
+        self.visit_part(visitor)
     }
 
     fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -162,7 +211,7 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut ComplexDeserializer<'a,
         V: serde::de::Visitor<'de>,
     {
         self.ensure_kind(ComplexKind::U32)?;
-        visitor.visit_u32(self.deserializer.get_u32_value()?)
+        self.visit_part(visitor)
     }
 
     fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -170,7 +219,7 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut ComplexDeserializer<'a,
         V: serde::de::Visitor<'de>,
     {
         self.ensure_kind(ComplexKind::U64)?;
-        visitor.visit_u64(self.deserializer.get_u64_value()?)
+        self.visit_part(visitor)
     }
 
     fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -178,7 +227,7 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut ComplexDeserializer<'a,
         V: serde::de::Visitor<'de>,
     {
         self.ensure_kind(ComplexKind::U128)?;
-        visitor.visit_u128(self.deserializer.get_u128_value()?)
+        self.visit_part(visitor)
     }
 
     fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -186,7 +235,7 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut ComplexDeserializer<'a,
         V: serde::de::Visitor<'de>,
     {
         self.ensure_kind(ComplexKind::F32)?;
-        visitor.visit_f32(self.deserializer.get_f32_value()?)
+        self.visit_part(visitor)
     }
 
     fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -194,11 +243,41 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut ComplexDeserializer<'a,
         V: serde::de::Visitor<'de>,
     {
         self.ensure_kind(ComplexKind::F64)?;
-        visitor.visit_f64(self.deserializer.get_f64_value()?)
+        self.visit_part(visitor)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    // A whole number, as an element of a complex array
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_seq(self)
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_seq(self)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.deserializer.human_readable
     }
 
     forward_to_deserialize_any! {
-        bool char str string bytes byte_buf option unit unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier ignored_any
+        bool char str string bytes byte_buf option unit unit_struct tuple_struct map struct enum identifier ignored_any
     }
 }
 
@@ -229,6 +308,7 @@ impl<'a, 'de, R: Read> SeqAccess<'de> for ComplexArrayDeserializer<'a, R> {
             Ok(None)
         } else {
             self.index += 1;
+            self.deserializer.index = 0;
             seed.deserialize(&mut self.deserializer).map(Some)
         }
     }
@@ -265,6 +345,10 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for ComplexArrayDeserializer<'a,
         visitor.visit_seq(self.deserializer)
     }
 
+    fn is_human_readable(&self) -> bool {
+        self.deserializer.deserializer.human_readable
+    }
+
     forward_to_deserialize_any! {
         i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 bool char str string bytes byte_buf option unit unit_struct newtype_struct tuple_struct map struct enum identifier ignored_any
     }
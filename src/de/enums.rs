@@ -10,8 +10,44 @@ use serde::{
 };
 use std::io::Read;
 
+/// How an enum variant was identified.
+pub enum Variant {
+    /// By its index, in a tag followed by its payload.
+    Index,
+    /// By its name, as the key of a single-field object holding its payload.
+    Named(String),
+    /// By its name, as a bare string. Only unit variants are written this way.
+    Unit(String),
+}
+
 pub struct EnumDeserializer<'a, R: Read> {
     pub deserializer: &'a mut Deserializer<R>,
+    pub variant: Variant,
+}
+
+impl<'a, R: Read> EnumDeserializer<'a, R> {
+    /// Adds the name of the variant, if known, to errors from deserializing its payload.
+    fn with_name<T>(&self, result: Result<T, Error>) -> Result<T, Error> {
+        match &self.variant {
+            Variant::Index => result,
+            Variant::Named(name) | Variant::Unit(name) => {
+                result.map_err(|source| Error::InvalidVariant {
+                    variant: name.clone(),
+                    source: Box::new(source),
+                })
+            }
+        }
+    }
+
+    fn expect_payload(&self) -> Result<(), Error> {
+        match self.variant {
+            Variant::Unit(_) => self.with_name(Err(Error::WrongType {
+                expected: "variant payload",
+                found: "unit variant",
+            })),
+            _ => Ok(()),
+        }
+    }
 }
 
 impl<'a, 'de, R: Read> EnumAccess<'de> for EnumDeserializer<'a, R> {
@@ -30,11 +66,16 @@ impl<'a, 'de, R: Read> VariantAccess<'de> for EnumDeserializer<'a, R> {
     type Error = Error;
 
     fn unit_variant(self) -> Result<(), Self::Error> {
-        if self.deserializer.get_byte()? != NULL {
-            Err(Error::WrongType {
+        if let Variant::Unit(_) = self.variant {
+            return Ok(());
+        }
+
+        let header = self.deserializer.get_byte()?;
+        if header != NULL {
+            self.with_name(Err(Error::WrongType {
                 expected: header_name(NULL),
-                found: header_name(self.deserializer.get_byte()?),
-            })
+                found: header_name(header),
+            }))
         } else {
             Ok(())
         }
@@ -44,14 +85,18 @@ impl<'a, 'de, R: Read> VariantAccess<'de> for EnumDeserializer<'a, R> {
     where
         T: serde::de::DeserializeSeed<'de>,
     {
-        seed.deserialize(self.deserializer)
+        self.expect_payload()?;
+        let result = seed.deserialize(&mut *self.deserializer);
+        self.with_name(result)
     }
 
     fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        self.deserializer.deserialize_tuple(len, visitor)
+        self.expect_payload()?;
+        let result = self.deserializer.deserialize_tuple(len, visitor);
+        self.with_name(result)
     }
 
     fn struct_variant<V>(
@@ -62,7 +107,9 @@ impl<'a, 'de, R: Read> VariantAccess<'de> for EnumDeserializer<'a, R> {
     where
         V: Visitor<'de>,
     {
-        self.deserializer.deserialize_struct("", fields, visitor)
+        self.expect_payload()?;
+        let result = self.deserializer.deserialize_struct("", fields, visitor);
+        self.with_name(result)
     }
 }
 
@@ -80,7 +127,14 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut EnumDeserializer<'a, R>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_u64(self.deserializer.get_size()? as u64)
+        match &self.variant {
+            Variant::Index => visitor.visit_u64(self.deserializer.get_size()? as u64),
+            Variant::Named(name) | Variant::Unit(name) => visitor.visit_str(name),
+        }
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.deserializer.human_readable
     }
 
     forward_to_deserialize_any! {
@@ -1,7 +1,7 @@
 use super::Deserializer;
-use crate::{Error, headers::ObjectKind};
+use crate::{Error, error::Violation, headers::ObjectKind};
 use serde::{
-    de::{MapAccess, Visitor},
+    de::{IntoDeserializer, MapAccess, Visitor},
     forward_to_deserialize_any,
 };
 use std::io::Read;
@@ -11,6 +11,18 @@ pub struct MapDeserializer<'a, R: Read> {
     len: usize,
     index: usize,
     kind: ObjectKind,
+    previous: Option<Key>,
+    fields: Option<&'static [&'static str]>,
+}
+
+/// A key, kept in strict mode to check that the keys of an object are sorted and unique.
+///
+/// Every key of an object is of the same kind, so only keys of the same variant are ever compared.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum Key {
+    Signed(i128),
+    Unsigned(u128),
+    String(Vec<u8>),
 }
 
 impl<'a, R: Read> MapDeserializer<'a, R> {
@@ -20,7 +32,61 @@ impl<'a, R: Read> MapDeserializer<'a, R> {
             len,
             kind,
             index: 0,
+            previous: None,
+            fields: None,
+        }
+    }
+
+    /// Creates a deserializer for the fields of a struct, whose integer keys are taken to be
+    /// indices into `fields`.
+    pub fn new_struct(
+        deserializer: &'a mut Deserializer<R>,
+        len: usize,
+        kind: ObjectKind,
+        fields: &'static [&'static str],
+    ) -> Self {
+        Self {
+            fields: Some(fields),
+            ..Self::new(deserializer, len, kind)
+        }
+    }
+
+    fn get_int_key(&mut self) -> Result<Key, Error> {
+        let key = match self.kind {
+            ObjectKind::I8 => Key::Signed(self.deserializer.get_i8_value()? as i128),
+            ObjectKind::I16 => Key::Signed(self.deserializer.get_i16_value()? as i128),
+            ObjectKind::I32 => Key::Signed(self.deserializer.get_i32_value()? as i128),
+            ObjectKind::I64 => Key::Signed(self.deserializer.get_i64_value()? as i128),
+            ObjectKind::I128 => Key::Signed(self.deserializer.get_i128_value()?),
+            ObjectKind::U8 => Key::Unsigned(self.deserializer.get_u8_value()? as u128),
+            ObjectKind::U16 => Key::Unsigned(self.deserializer.get_u16_value()? as u128),
+            ObjectKind::U32 => Key::Unsigned(self.deserializer.get_u32_value()? as u128),
+            ObjectKind::U64 => Key::Unsigned(self.deserializer.get_u64_value()? as u128),
+            ObjectKind::U128 => Key::Unsigned(self.deserializer.get_u128_value()?),
+            ObjectKind::String => unreachable!(),
+        };
+        Ok(key)
+    }
+
+    fn check_key(&mut self, key: impl FnOnce() -> Key) -> Result<(), Error> {
+        if !self.deserializer.strict {
+            return Ok(());
+        }
+
+        let key = key();
+        if let Some(previous) = &self.previous {
+            match previous.cmp(&key) {
+                std::cmp::Ordering::Less => {}
+                std::cmp::Ordering::Equal => {
+                    return Err(Error::NonCanonical(Violation::DuplicateKey));
+                }
+                std::cmp::Ordering::Greater => {
+                    return Err(Error::NonCanonical(Violation::UnsortedKeys));
+                }
+            }
         }
+        self.previous = Some(key);
+        Ok(())
     }
 }
 
@@ -74,7 +140,11 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut MapDeserializer<'a, R>
         V: Visitor<'de>,
     {
         match self.kind {
-            ObjectKind::I8 => visitor.visit_i8(self.deserializer.get_i8_value()?),
+            ObjectKind::I8 => {
+                let v = self.deserializer.get_i8_value()?;
+                self.check_key(|| Key::Signed(v as i128))?;
+                visitor.visit_i8(v)
+            }
             ObjectKind::String => self.deserialize_string(visitor),
             found => Err(Error::MismatchedKeyType {
                 expected: ObjectKind::I8,
@@ -88,7 +158,11 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut MapDeserializer<'a, R>
         V: Visitor<'de>,
     {
         match self.kind {
-            ObjectKind::I16 => visitor.visit_i16(self.deserializer.get_i16_value()?),
+            ObjectKind::I16 => {
+                let v = self.deserializer.get_i16_value()?;
+                self.check_key(|| Key::Signed(v as i128))?;
+                visitor.visit_i16(v)
+            }
             ObjectKind::String => self.deserialize_string(visitor),
             found => Err(Error::MismatchedKeyType {
                 expected: ObjectKind::I16,
@@ -102,7 +176,11 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut MapDeserializer<'a, R>
         V: Visitor<'de>,
     {
         match self.kind {
-            ObjectKind::I32 => visitor.visit_i32(self.deserializer.get_i32_value()?),
+            ObjectKind::I32 => {
+                let v = self.deserializer.get_i32_value()?;
+                self.check_key(|| Key::Signed(v as i128))?;
+                visitor.visit_i32(v)
+            }
             ObjectKind::String => self.deserialize_string(visitor),
             found => Err(Error::MismatchedKeyType {
                 expected: ObjectKind::I32,
@@ -116,7 +194,11 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut MapDeserializer<'a, R>
         V: Visitor<'de>,
     {
         match self.kind {
-            ObjectKind::I64 => visitor.visit_i64(self.deserializer.get_i64_value()?),
+            ObjectKind::I64 => {
+                let v = self.deserializer.get_i64_value()?;
+                self.check_key(|| Key::Signed(v as i128))?;
+                visitor.visit_i64(v)
+            }
             ObjectKind::String => self.deserialize_string(visitor),
             found => Err(Error::MismatchedKeyType {
                 expected: ObjectKind::I64,
@@ -130,7 +212,11 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut MapDeserializer<'a, R>
         V: Visitor<'de>,
     {
         match self.kind {
-            ObjectKind::I128 => visitor.visit_i128(self.deserializer.get_i128_value()?),
+            ObjectKind::I128 => {
+                let v = self.deserializer.get_i128_value()?;
+                self.check_key(|| Key::Signed(v))?;
+                visitor.visit_i128(v)
+            }
             ObjectKind::String => self.deserialize_string(visitor),
             found => Err(Error::MismatchedKeyType {
                 expected: ObjectKind::I128,
@@ -144,7 +230,11 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut MapDeserializer<'a, R>
         V: Visitor<'de>,
     {
         match self.kind {
-            ObjectKind::U8 => visitor.visit_u8(self.deserializer.get_u8_value()?),
+            ObjectKind::U8 => {
+                let v = self.deserializer.get_u8_value()?;
+                self.check_key(|| Key::Unsigned(v as u128))?;
+                visitor.visit_u8(v)
+            }
             ObjectKind::String => self.deserialize_string(visitor),
             found => Err(Error::MismatchedKeyType {
                 expected: ObjectKind::U8,
@@ -158,7 +248,11 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut MapDeserializer<'a, R>
         V: Visitor<'de>,
     {
         match self.kind {
-            ObjectKind::U16 => visitor.visit_u16(self.deserializer.get_u16_value()?),
+            ObjectKind::U16 => {
+                let v = self.deserializer.get_u16_value()?;
+                self.check_key(|| Key::Unsigned(v as u128))?;
+                visitor.visit_u16(v)
+            }
             ObjectKind::String => self.deserialize_string(visitor),
             found => Err(Error::MismatchedKeyType {
                 expected: ObjectKind::U16,
@@ -172,7 +266,11 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut MapDeserializer<'a, R>
         V: Visitor<'de>,
     {
         match self.kind {
-            ObjectKind::U32 => visitor.visit_u32(self.deserializer.get_u32_value()?),
+            ObjectKind::U32 => {
+                let v = self.deserializer.get_u32_value()?;
+                self.check_key(|| Key::Unsigned(v as u128))?;
+                visitor.visit_u32(v)
+            }
             ObjectKind::String => self.deserialize_string(visitor),
             found => Err(Error::MismatchedKeyType {
                 expected: ObjectKind::U32,
@@ -186,7 +284,11 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut MapDeserializer<'a, R>
         V: Visitor<'de>,
     {
         match self.kind {
-            ObjectKind::U64 => visitor.visit_u64(self.deserializer.get_u64_value()?),
+            ObjectKind::U64 => {
+                let v = self.deserializer.get_u64_value()?;
+                self.check_key(|| Key::Unsigned(v as u128))?;
+                visitor.visit_u64(v)
+            }
             ObjectKind::String => self.deserialize_string(visitor),
             found => Err(Error::MismatchedKeyType {
                 expected: ObjectKind::U64,
@@ -200,7 +302,11 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut MapDeserializer<'a, R>
         V: Visitor<'de>,
     {
         match self.kind {
-            ObjectKind::U128 => visitor.visit_u128(self.deserializer.get_u128_value()?),
+            ObjectKind::U128 => {
+                let v = self.deserializer.get_u128_value()?;
+                self.check_key(|| Key::Unsigned(v))?;
+                visitor.visit_u128(v)
+            }
             ObjectKind::String => self.deserialize_string(visitor),
             found => Err(Error::MismatchedKeyType {
                 expected: ObjectKind::U128,
@@ -214,7 +320,11 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut MapDeserializer<'a, R>
         V: Visitor<'de>,
     {
         match self.kind {
-            ObjectKind::String => visitor.visit_string(self.deserializer.get_string_value()?),
+            ObjectKind::String => {
+                let v = self.deserializer.get_string_value()?;
+                self.check_key(|| Key::String(v.as_bytes().to_vec()))?;
+                visitor.visit_string(v)
+            }
             found => Err(Error::MismatchedKeyType {
                 expected: ObjectKind::String,
                 found,
@@ -222,7 +332,70 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut MapDeserializer<'a, R>
         }
     }
 
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// Enums are keyed by the names of their unit variants.
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.kind {
+            ObjectKind::String => {
+                let v = self.deserializer.get_string_value()?;
+                self.check_key(|| Key::String(v.as_bytes().to_vec()))?;
+                visitor.visit_enum(v.into_deserializer())
+            }
+            found => Err(Error::MismatchedKeyType {
+                expected: ObjectKind::String,
+                found,
+            }),
+        }
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let Some(fields) = self.fields else {
+            return self.deserialize_any(visitor);
+        };
+        if self.kind == ObjectKind::String {
+            return self.deserialize_string(visitor);
+        }
+
+        // Integer keys of a struct are the indices of its fields
+        let key = self.get_int_key()?;
+        let index = match key {
+            Key::Signed(v) => u64::try_from(v).unwrap_or(u64::MAX),
+            Key::Unsigned(v) => u64::try_from(v).unwrap_or(u64::MAX),
+            Key::String(_) => unreachable!(),
+        };
+        self.check_key(|| key)?;
+        match usize::try_from(index).ok().and_then(|i| fields.get(i)) {
+            Some(field) => visitor.visit_str(field),
+            None => visitor.visit_u64(index),
+        }
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.deserializer.human_readable
+    }
+
     forward_to_deserialize_any! {
-        bool f32 f64 char str bytes byte_buf option unit unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier ignored_any
+        bool f32 f64 char str bytes byte_buf option unit unit_struct seq tuple tuple_struct map struct ignored_any
     }
 }
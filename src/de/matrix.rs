@@ -1,28 +1,64 @@
 use super::Deserializer;
-use crate::{ArrayKind, Error, ObjectKind, headers::*};
-use serde::{de::MapAccess, forward_to_deserialize_any};
+use crate::{Error, headers::*, matrix::MatrixLayout};
+use serde::de::{DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess};
 use std::io::Read;
 
+const KEYS: [&str; 3] = ["layout", "extents", "value"];
+
+/// Reads the layout, extents, and values of a matrix, either as a map keyed by [`KEYS`] or as a
+/// sequence, as a [`Matrix`](crate::Matrix) reads them.
 pub struct MatrixDeserializer<'a, R: Read> {
     deserializer: &'a mut Deserializer<R>,
-    layout: String,
-    key: bool,
+    layout: MatrixLayout,
     index: usize,
-    extent_type: Option<ArrayKind>,
-    value_type: Option<ArrayKind>,
 }
 
 impl<'a, R: Read> MatrixDeserializer<'a, R> {
-    pub fn new(deserializer: &'a mut Deserializer<R>, layout: String) -> Self {
+    pub fn new(deserializer: &'a mut Deserializer<R>, layout: MatrixLayout) -> Self {
         Self {
             deserializer,
             layout,
-            key: false,
             index: 0,
-            extent_type: None,
-            value_type: None,
         }
     }
+
+    /// Deserializes the extents or values, after checking that they are typed arrays of the types
+    /// allowed for each.
+    fn deserialize_array<'de, T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<T::Value, Error> {
+        let header = self.deserializer.peek_byte()?;
+        let valid = if self.index == 1 {
+            matches!(
+                header,
+                U8_ARRAY | U16_ARRAY | U32_ARRAY | U64_ARRAY | U128_ARRAY
+            )
+        } else {
+            matches!(
+                header,
+                BF16_ARRAY
+                    | F16_ARRAY
+                    | F32_ARRAY
+                    | F64_ARRAY
+                    | I8_ARRAY
+                    | I16_ARRAY
+                    | I32_ARRAY
+                    | I64_ARRAY
+                    | I128_ARRAY
+                    | U8_ARRAY
+                    | U16_ARRAY
+                    | U32_ARRAY
+                    | U64_ARRAY
+                    | U128_ARRAY
+                    | COMPLEX
+            )
+        };
+        if !valid {
+            return Err(Error::InvalidMatrixType);
+        }
+        seed.deserialize(&mut *self.deserializer)
+    }
 }
 
 impl<'a, 'de, R: Read> MapAccess<'de> for MatrixDeserializer<'a, R> {
@@ -30,558 +66,48 @@ impl<'a, 'de, R: Read> MapAccess<'de> for MatrixDeserializer<'a, R> {
 
     fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
     where
-        K: serde::de::DeserializeSeed<'de>,
+        K: DeserializeSeed<'de>,
     {
-        match self.index {
-            0 => {}
-            1 => {
-                self.extent_type = Some(match self.deserializer.get_byte()? {
-                    U8_ARRAY => ArrayKind::U8,
-                    U16_ARRAY => ArrayKind::U16,
-                    U32_ARRAY => ArrayKind::U32,
-                    U64_ARRAY => ArrayKind::U64,
-                    U128_ARRAY => ArrayKind::U128,
-                    _ => {
-                        return Err(Error::InvalidMatrixType);
-                    }
-                })
-            }
-            2 => {
-                self.value_type = Some(match self.deserializer.get_byte()? {
-                    U8_ARRAY => ArrayKind::U8,
-                    U16_ARRAY => ArrayKind::U16,
-                    U32_ARRAY => ArrayKind::U32,
-                    U64_ARRAY => ArrayKind::U64,
-                    U128_ARRAY => ArrayKind::U128,
-                    _ => {
-                        return Err(Error::InvalidMatrixType);
-                    }
-                })
-            }
-            3 => {
-                return Ok(None);
-            }
-            _ => unreachable!(),
+        match KEYS.get(self.index) {
+            Some(key) => seed.deserialize(key.into_deserializer()).map(Some),
+            None => Ok(None),
         }
-        self.key = true;
-        seed.deserialize(self).map(Some)
     }
 
     fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
     where
-        V: serde::de::DeserializeSeed<'de>,
+        V: DeserializeSeed<'de>,
     {
-        self.key = false;
-        let out = seed.deserialize(&mut *self);
+        let out = match self.index {
+            0 => seed.deserialize(self.layout.name().into_deserializer()),
+            _ => self.deserialize_array(seed),
+        };
         self.index += 1;
         out
     }
-}
-
-impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut MatrixDeserializer<'a, R> {
-    type Error = Error;
-
-    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        Err(Error::InvalidMatrixType)
-    }
-
-    fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        match self.index {
-            0 => Err(Error::InvalidMatrixType),
-            1 => {
-                if self.key {
-                    Err(Error::MismatchedKeyType {
-                        expected: ObjectKind::String,
-                        found: ObjectKind::I8,
-                    })
-                } else {
-                    Err(Error::InvalidMatrixType)
-                }
-            }
-            2 => {
-                if self.key {
-                    Err(Error::MismatchedKeyType {
-                        expected: ObjectKind::String,
-                        found: ObjectKind::I8,
-                    })
-                } else {
-                    match self.value_type.unwrap() {
-                        ArrayKind::I8 => self.deserializer.deserialize_i8_array(visitor),
-                        found => Err(Error::MismatchedElementType {
-                            expected: ArrayKind::I8,
-                            found,
-                        }),
-                    }
-                }
-            }
-            _ => unreachable!(),
-        }
-    }
-
-    fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        match self.index {
-            0 => Err(Error::InvalidMatrixType),
-            1 => {
-                if self.key {
-                    Err(Error::MismatchedKeyType {
-                        expected: ObjectKind::String,
-                        found: ObjectKind::I16,
-                    })
-                } else {
-                    Err(Error::InvalidMatrixType)
-                }
-            }
-            2 => {
-                if self.key {
-                    Err(Error::MismatchedKeyType {
-                        expected: ObjectKind::String,
-                        found: ObjectKind::I16,
-                    })
-                } else {
-                    match self.value_type.unwrap() {
-                        ArrayKind::I16 => self.deserializer.deserialize_i16_array(visitor),
-                        found => Err(Error::MismatchedElementType {
-                            expected: ArrayKind::I16,
-                            found,
-                        }),
-                    }
-                }
-            }
-            _ => unreachable!(),
-        }
-    }
-
-    fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        match self.index {
-            0 => Err(Error::InvalidMatrixType),
-            1 => {
-                if self.key {
-                    Err(Error::MismatchedKeyType {
-                        expected: ObjectKind::String,
-                        found: ObjectKind::I32,
-                    })
-                } else {
-                    Err(Error::InvalidMatrixType)
-                }
-            }
-            2 => {
-                if self.key {
-                    Err(Error::MismatchedKeyType {
-                        expected: ObjectKind::String,
-                        found: ObjectKind::I32,
-                    })
-                } else {
-                    match self.value_type.unwrap() {
-                        ArrayKind::I32 => self.deserializer.deserialize_i32_array(visitor),
-                        found => Err(Error::MismatchedElementType {
-                            expected: ArrayKind::I32,
-                            found,
-                        }),
-                    }
-                }
-            }
-            _ => unreachable!(),
-        }
-    }
-
-    fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        match self.index {
-            0 => Err(Error::InvalidMatrixType),
-            1 => {
-                if self.key {
-                    Err(Error::MismatchedKeyType {
-                        expected: ObjectKind::String,
-                        found: ObjectKind::I64,
-                    })
-                } else {
-                    Err(Error::InvalidMatrixType)
-                }
-            }
-            2 => {
-                if self.key {
-                    Err(Error::MismatchedKeyType {
-                        expected: ObjectKind::String,
-                        found: ObjectKind::I64,
-                    })
-                } else {
-                    match self.value_type.unwrap() {
-                        ArrayKind::I64 => self.deserializer.deserialize_i64_array(visitor),
-                        found => Err(Error::MismatchedElementType {
-                            expected: ArrayKind::I64,
-                            found,
-                        }),
-                    }
-                }
-            }
-            _ => unreachable!(),
-        }
-    }
-
-    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        match self.index {
-            0 => Err(Error::InvalidMatrixType),
-            1 => {
-                if self.key {
-                    Err(Error::MismatchedKeyType {
-                        expected: ObjectKind::String,
-                        found: ObjectKind::I128,
-                    })
-                } else {
-                    Err(Error::InvalidMatrixType)
-                }
-            }
-            2 => {
-                if self.key {
-                    Err(Error::MismatchedKeyType {
-                        expected: ObjectKind::String,
-                        found: ObjectKind::I128,
-                    })
-                } else {
-                    match self.value_type.unwrap() {
-                        ArrayKind::I128 => self.deserializer.deserialize_i128_array(visitor),
-                        found => Err(Error::MismatchedElementType {
-                            expected: ArrayKind::I128,
-                            found,
-                        }),
-                    }
-                }
-            }
-            _ => unreachable!(),
-        }
-    }
-
-    fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        match self.index {
-            0 => Err(Error::InvalidMatrixType),
-            1 => {
-                if self.key {
-                    Err(Error::MismatchedKeyType {
-                        expected: ObjectKind::String,
-                        found: ObjectKind::U8,
-                    })
-                } else {
-                    match self.extent_type.unwrap() {
-                        ArrayKind::U8 => self.deserializer.deserialize_u8_array(visitor),
-                        found => Err(Error::MismatchedElementType {
-                            expected: ArrayKind::U8,
-                            found,
-                        }),
-                    }
-                }
-            }
-            2 => {
-                if self.key {
-                    Err(Error::MismatchedKeyType {
-                        expected: ObjectKind::String,
-                        found: ObjectKind::U8,
-                    })
-                } else {
-                    match self.value_type.unwrap() {
-                        ArrayKind::U8 => self.deserializer.deserialize_u8_array(visitor),
-                        found => Err(Error::MismatchedElementType {
-                            expected: ArrayKind::U8,
-                            found,
-                        }),
-                    }
-                }
-            }
-            _ => unreachable!(),
-        }
-    }
-
-    fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        match self.index {
-            0 => Err(Error::InvalidMatrixType),
-            1 => {
-                if self.key {
-                    Err(Error::MismatchedKeyType {
-                        expected: ObjectKind::String,
-                        found: ObjectKind::U16,
-                    })
-                } else {
-                    match self.extent_type.unwrap() {
-                        ArrayKind::U16 => self.deserializer.deserialize_u16_array(visitor),
-                        found => Err(Error::MismatchedElementType {
-                            expected: ArrayKind::U16,
-                            found,
-                        }),
-                    }
-                }
-            }
-            2 => {
-                if self.key {
-                    Err(Error::MismatchedKeyType {
-                        expected: ObjectKind::String,
-                        found: ObjectKind::U16,
-                    })
-                } else {
-                    match self.value_type.unwrap() {
-                        ArrayKind::U16 => self.deserializer.deserialize_u16_array(visitor),
-                        found => Err(Error::MismatchedElementType {
-                            expected: ArrayKind::U16,
-                            found,
-                        }),
-                    }
-                }
-            }
-            _ => unreachable!(),
-        }
-    }
-
-    fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        match self.index {
-            0 => Err(Error::InvalidMatrixType),
-            1 => {
-                if self.key {
-                    Err(Error::MismatchedKeyType {
-                        expected: ObjectKind::String,
-                        found: ObjectKind::U32,
-                    })
-                } else {
-                    match self.extent_type.unwrap() {
-                        ArrayKind::U32 => self.deserializer.deserialize_u32_array(visitor),
-                        found => Err(Error::MismatchedElementType {
-                            expected: ArrayKind::U32,
-                            found,
-                        }),
-                    }
-                }
-            }
-            2 => {
-                if self.key {
-                    Err(Error::MismatchedKeyType {
-                        expected: ObjectKind::String,
-                        found: ObjectKind::U32,
-                    })
-                } else {
-                    match self.value_type.unwrap() {
-                        ArrayKind::U32 => self.deserializer.deserialize_u32_array(visitor),
-                        found => Err(Error::MismatchedElementType {
-                            expected: ArrayKind::U32,
-                            found,
-                        }),
-                    }
-                }
-            }
-            _ => unreachable!(),
-        }
-    }
-
-    fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        match self.index {
-            0 => Err(Error::InvalidMatrixType),
-            1 => {
-                if self.key {
-                    Err(Error::MismatchedKeyType {
-                        expected: ObjectKind::String,
-                        found: ObjectKind::U64,
-                    })
-                } else {
-                    match self.extent_type.unwrap() {
-                        ArrayKind::U64 => self.deserializer.deserialize_u64_array(visitor),
-                        found => Err(Error::MismatchedElementType {
-                            expected: ArrayKind::U64,
-                            found,
-                        }),
-                    }
-                }
-            }
-            2 => {
-                if self.key {
-                    Err(Error::MismatchedKeyType {
-                        expected: ObjectKind::String,
-                        found: ObjectKind::U64,
-                    })
-                } else {
-                    match self.value_type.unwrap() {
-                        ArrayKind::U64 => self.deserializer.deserialize_u64_array(visitor),
-                        found => Err(Error::MismatchedElementType {
-                            expected: ArrayKind::U64,
-                            found,
-                        }),
-                    }
-                }
-            }
-            _ => unreachable!(),
-        }
-    }
-
-    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        match self.index {
-            0 => Err(Error::InvalidMatrixType),
-            1 => {
-                if self.key {
-                    Err(Error::MismatchedKeyType {
-                        expected: ObjectKind::String,
-                        found: ObjectKind::U128,
-                    })
-                } else {
-                    match self.extent_type.unwrap() {
-                        ArrayKind::U128 => self.deserializer.deserialize_u128_array(visitor),
-                        found => Err(Error::MismatchedElementType {
-                            expected: ArrayKind::U128,
-                            found,
-                        }),
-                    }
-                }
-            }
-            2 => {
-                if self.key {
-                    Err(Error::MismatchedKeyType {
-                        expected: ObjectKind::String,
-                        found: ObjectKind::U128,
-                    })
-                } else {
-                    match self.value_type.unwrap() {
-                        ArrayKind::U128 => self.deserializer.deserialize_u128_array(visitor),
-                        found => Err(Error::MismatchedElementType {
-                            expected: ArrayKind::U128,
-                            found,
-                        }),
-                    }
-                }
-            }
-            _ => unreachable!(),
-        }
-    }
 
-    fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        match self.index {
-            0 => Err(Error::InvalidMatrixType),
-            1 => {
-                if self.key {
-                    Err(Error::InvalidKey)
-                } else {
-                    Err(Error::InvalidMatrixType)
-                }
-            }
-            2 => {
-                if self.key {
-                    Err(Error::InvalidKey)
-                } else {
-                    match self.value_type.unwrap() {
-                        ArrayKind::F32 => self.deserializer.deserialize_f32_array(visitor),
-                        found => Err(Error::MismatchedElementType {
-                            expected: ArrayKind::F32,
-                            found,
-                        }),
-                    }
-                }
-            }
-            _ => unreachable!(),
-        }
-    }
-
-    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        match self.index {
-            0 => Err(Error::InvalidMatrixType),
-            1 => {
-                if self.key {
-                    Err(Error::InvalidKey)
-                } else {
-                    Err(Error::InvalidMatrixType)
-                }
-            }
-            2 => {
-                if self.key {
-                    Err(Error::InvalidKey)
-                } else {
-                    match self.value_type.unwrap() {
-                        ArrayKind::F64 => self.deserializer.deserialize_f64_array(visitor),
-                        found => Err(Error::MismatchedElementType {
-                            expected: ArrayKind::F64,
-                            found,
-                        }),
-                    }
-                }
-            }
-            _ => unreachable!(),
-        }
+    fn size_hint(&self) -> Option<usize> {
+        Some(KEYS.len() - self.index)
     }
+}
 
-    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        self.deserialize_str(visitor)
-    }
+impl<'a, 'de, R: Read> SeqAccess<'de> for MatrixDeserializer<'a, R> {
+    type Error = Error;
 
-    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
     where
-        V: serde::de::Visitor<'de>,
+        T: DeserializeSeed<'de>,
     {
-        match self.index {
-            0 => {
-                if self.key {
-                    visitor.visit_str("layout")
-                } else {
-                    visitor.visit_str(&self.layout)
-                }
-            }
-            1 => {
-                if self.key {
-                    visitor.visit_str("extents")
-                } else {
-                    Err(Error::MismatchedElementType {
-                        expected: self.extent_type.unwrap(),
-                        found: ArrayKind::String,
-                    })
-                }
-            }
-            2 => {
-                if self.key {
-                    visitor.visit_str("value")
-                } else {
-                    Err(Error::MismatchedElementType {
-                        expected: self.value_type.unwrap(),
-                        found: ArrayKind::String,
-                    })
-                }
-            }
-            _ => unreachable!(),
-        }
+        let out = match self.index {
+            0 => seed.deserialize(self.layout.bit().into_deserializer()),
+            1 | 2 => self.deserialize_array(seed),
+            _ => return Ok(None),
+        };
+        self.index += 1;
+        out.map(Some)
     }
 
-    forward_to_deserialize_any! {
-        bool char bytes byte_buf option unit unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier ignored_any
+    fn size_hint(&self) -> Option<usize> {
+        Some(KEYS.len() - self.index)
     }
 }
@@ -1,5 +1,10 @@
-use super::Deserializer;
+use super::{
+    Deserializer,
+    enums::{EnumDeserializer, Variant},
+};
 use crate::{error::Error, headers::ArrayKind};
+#[cfg(feature = "half")]
+use serde::de::IntoDeserializer;
 use serde::{
     de::{SeqAccess, Visitor},
     forward_to_deserialize_any,
@@ -70,6 +75,7 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut SeqDeserializer<'a, R>
             ArrayKind::U128 => self.deserialize_u128(visitor),
             ArrayKind::F32 => self.deserialize_f32(visitor),
             ArrayKind::F64 => self.deserialize_f64(visitor),
+            ArrayKind::F128 => visitor.visit_bytes(&self.deserializer.get_f128_value()?),
             ArrayKind::Complex => unreachable!(),
         }
     }
@@ -94,6 +100,7 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut SeqDeserializer<'a, R>
     {
         match self.kind {
             ArrayKind::I16 => visitor.visit_i16(self.deserializer.get_i16_value()?),
+            ArrayKind::I8 => visitor.visit_i8(self.deserializer.get_i8_value()?),
             ArrayKind::Generic => self.deserializer.deserialize_i16(visitor),
             found => Err(Error::MismatchedElementType {
                 expected: ArrayKind::I16,
@@ -108,6 +115,8 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut SeqDeserializer<'a, R>
     {
         match self.kind {
             ArrayKind::I32 => visitor.visit_i32(self.deserializer.get_i32_value()?),
+            ArrayKind::I8 => visitor.visit_i8(self.deserializer.get_i8_value()?),
+            ArrayKind::I16 => visitor.visit_i16(self.deserializer.get_i16_value()?),
             ArrayKind::Generic => self.deserializer.deserialize_i32(visitor),
             found => Err(Error::MismatchedElementType {
                 expected: ArrayKind::I32,
@@ -122,6 +131,9 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut SeqDeserializer<'a, R>
     {
         match self.kind {
             ArrayKind::I64 => visitor.visit_i64(self.deserializer.get_i64_value()?),
+            ArrayKind::I8 => visitor.visit_i8(self.deserializer.get_i8_value()?),
+            ArrayKind::I16 => visitor.visit_i16(self.deserializer.get_i16_value()?),
+            ArrayKind::I32 => visitor.visit_i32(self.deserializer.get_i32_value()?),
             ArrayKind::Generic => self.deserializer.deserialize_i64(visitor),
             found => Err(Error::MismatchedElementType {
                 expected: ArrayKind::I64,
@@ -136,6 +148,10 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut SeqDeserializer<'a, R>
     {
         match self.kind {
             ArrayKind::I128 => visitor.visit_i128(self.deserializer.get_i128_value()?),
+            ArrayKind::I8 => visitor.visit_i8(self.deserializer.get_i8_value()?),
+            ArrayKind::I16 => visitor.visit_i16(self.deserializer.get_i16_value()?),
+            ArrayKind::I32 => visitor.visit_i32(self.deserializer.get_i32_value()?),
+            ArrayKind::I64 => visitor.visit_i64(self.deserializer.get_i64_value()?),
             ArrayKind::Generic => self.deserializer.deserialize_i128(visitor),
             found => Err(Error::MismatchedElementType {
                 expected: ArrayKind::I128,
@@ -164,6 +180,7 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut SeqDeserializer<'a, R>
     {
         match self.kind {
             ArrayKind::U16 => visitor.visit_u16(self.deserializer.get_u16_value()?),
+            ArrayKind::U8 => visitor.visit_u8(self.deserializer.get_u8_value()?),
             ArrayKind::Generic => self.deserializer.deserialize_u16(visitor),
             found => Err(Error::MismatchedElementType {
                 expected: ArrayKind::U16,
@@ -178,6 +195,8 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut SeqDeserializer<'a, R>
     {
         match self.kind {
             ArrayKind::U32 => visitor.visit_u32(self.deserializer.get_u32_value()?),
+            ArrayKind::U8 => visitor.visit_u8(self.deserializer.get_u8_value()?),
+            ArrayKind::U16 => visitor.visit_u16(self.deserializer.get_u16_value()?),
             ArrayKind::Generic => self.deserializer.deserialize_u32(visitor),
             found => Err(Error::MismatchedElementType {
                 expected: ArrayKind::U32,
@@ -192,6 +211,9 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut SeqDeserializer<'a, R>
     {
         match self.kind {
             ArrayKind::U64 => visitor.visit_u64(self.deserializer.get_u64_value()?),
+            ArrayKind::U8 => visitor.visit_u8(self.deserializer.get_u8_value()?),
+            ArrayKind::U16 => visitor.visit_u16(self.deserializer.get_u16_value()?),
+            ArrayKind::U32 => visitor.visit_u32(self.deserializer.get_u32_value()?),
             ArrayKind::Generic => self.deserializer.deserialize_u64(visitor),
             found => Err(Error::MismatchedElementType {
                 expected: ArrayKind::U64,
@@ -206,6 +228,10 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut SeqDeserializer<'a, R>
     {
         match self.kind {
             ArrayKind::U128 => visitor.visit_u128(self.deserializer.get_u128_value()?),
+            ArrayKind::U8 => visitor.visit_u8(self.deserializer.get_u8_value()?),
+            ArrayKind::U16 => visitor.visit_u16(self.deserializer.get_u16_value()?),
+            ArrayKind::U32 => visitor.visit_u32(self.deserializer.get_u32_value()?),
+            ArrayKind::U64 => visitor.visit_u64(self.deserializer.get_u64_value()?),
             ArrayKind::Generic => self.deserializer.deserialize_u128(visitor),
             found => Err(Error::MismatchedElementType {
                 expected: ArrayKind::U128,
@@ -220,6 +246,8 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut SeqDeserializer<'a, R>
     {
         match self.kind {
             ArrayKind::F32 => visitor.visit_f32(self.deserializer.get_f32_value()?),
+            ArrayKind::BF16 => visitor.visit_f32(self.deserializer.get_bf16_value()?),
+            ArrayKind::F16 => visitor.visit_f32(self.deserializer.get_f16_value()?),
             ArrayKind::Generic => self.deserializer.deserialize_f32(visitor),
             found => Err(Error::MismatchedElementType {
                 expected: ArrayKind::F32,
@@ -234,6 +262,9 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut SeqDeserializer<'a, R>
     {
         match self.kind {
             ArrayKind::F64 => visitor.visit_f64(self.deserializer.get_f64_value()?),
+            ArrayKind::F32 => visitor.visit_f32(self.deserializer.get_f32_value()?),
+            ArrayKind::BF16 => visitor.visit_f32(self.deserializer.get_bf16_value()?),
+            ArrayKind::F16 => visitor.visit_f32(self.deserializer.get_f16_value()?),
             ArrayKind::Generic => self.deserializer.deserialize_f64(visitor),
             found => Err(Error::MismatchedElementType {
                 expected: ArrayKind::F64,
@@ -248,15 +279,20 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut SeqDeserializer<'a, R>
     {
         match self.kind {
             ArrayKind::Boolean => {
-                let sub_index = self.index % 8;
-                let byte = if sub_index == 0 {
+                // `index` has already moved past this element
+                let index = self.index - 1;
+                // Each byte is consumed along with the last boolean it holds
+                let byte = if index % 8 == 7 || self.index == self.len {
                     self.deserializer.get_byte()?
                 } else {
                     self.deserializer.peek_byte()?
                 };
-                let bit = byte & (1 << sub_index);
 
-                visitor.visit_bool(bit != 0)
+                let byte = self.deserializer.bool_array_byte(byte);
+                if self.index == self.len {
+                    self.deserializer.check_bool_array_padding(self.len, byte)?;
+                }
+                visitor.visit_bool(crate::bits::unpack(byte, index))
             }
             ArrayKind::Generic => self.deserializer.deserialize_bool(visitor),
             found => Err(Error::MismatchedElementType {
@@ -280,7 +316,86 @@ impl<'a, 'de, R: Read> serde::Deserializer<'de> for &mut SeqDeserializer<'a, R>
         }
     }
 
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.kind {
+            ArrayKind::Generic => self.deserializer.deserialize_option(visitor),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.kind {
+            ArrayKind::Generic => self.deserializer.deserialize_struct(name, fields, visitor),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.kind {
+            ArrayKind::Generic => self.deserializer.deserialize_enum(name, variants, visitor),
+            // Unit variants written by name
+            ArrayKind::String => {
+                let name = self.deserializer.get_string_value()?;
+                visitor.visit_enum(EnumDeserializer {
+                    deserializer: self.deserializer,
+                    variant: Variant::Unit(name),
+                })
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.deserializer.human_readable
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match (self.kind, name) {
+            (ArrayKind::Generic, _) => self.deserializer.deserialize_newtype_struct(name, visitor),
+            // `half::f16` and `half::bf16` deserialize from newtype structs of their bits
+            #[cfg(feature = "half")]
+            (ArrayKind::F16, "f16") | (ArrayKind::BF16, "bf16") => {
+                let bits = if name == "f16" {
+                    self.deserializer.get_f16_bits()?
+                } else {
+                    self.deserializer.get_bf16_bits()?
+                };
+                visitor.visit_newtype_struct(bits.into_deserializer())
+            }
+            (ArrayKind::F16 | ArrayKind::BF16 | ArrayKind::F128, _) => {
+                self.deserialize_any(visitor)
+            }
+            _ => visitor.visit_newtype_struct(self),
+        }
+    }
+
     forward_to_deserialize_any! {
-        char str bytes byte_buf option unit unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier ignored_any
+        char str bytes byte_buf unit unit_struct seq tuple tuple_struct map identifier ignored_any
     }
 }
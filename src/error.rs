@@ -21,16 +21,24 @@ pub enum Error {
         std::io::Error,
     ),
 
+    #[error("Output buffer too small")]
+    /// Returned when serializing into a slice that is too small to hold the output.
+    ///
+    /// `needed` holds the total number of bytes the output requires, when it is known.
+    BufferFull { needed: Option<usize> },
+
     #[error("Keys must be strings or integers")]
     /// Returned when attempting to serialize an object key that is not a string or integer.
+    ///
+    /// Characters and unit enum variants are written as string keys, the latter by name, and
+    /// newtype structs as the key they wrap.
     InvalidKey,
 
     #[error("Unsupported data type: {0}")]
     /// Returned when trying to deserialize an unsupported data type.
     ///
-    /// This can occur when trying to deserialize an [`f16`](half::f16) or a [`bf16`](half::bf16)
-    /// without the `half` feature enabled. It can also occur when trying to deserialize a 128-bit
-    /// float, as these are not yet stable in Rust.
+    /// This can occur when trying to serialize or deserialize an [`f16`](half::f16) or a
+    /// [`bf16`](half::bf16) without the `half` feature enabled.
     UnsupportedDataType(SpecialType),
 
     #[error("Invalid type. Expected {expected}, found {found}.")]
@@ -66,6 +74,39 @@ pub enum Error {
     /// (e.g. trying to deserialize a 62-bit size on a 32-bit platform).
     TooLong,
 
+    #[error("Sequences and maps must have a known length when streaming")]
+    /// Returned when a [`StreamingSerializer`](crate::ser::StreamingSerializer) is given a
+    /// sequence or map without a length, since the size must be written before any element.
+    UnknownLength,
+
+    #[error("Expected {expected} elements, found {found}")]
+    /// Returned when a sequence or map given to a
+    /// [`StreamingSerializer`](crate::ser::StreamingSerializer) yields a different number of
+    /// elements than its declared length.
+    LengthMismatch { expected: usize, found: usize },
+
+    #[error("Field `{0}` cannot be skipped when structs are written as arrays")]
+    /// Returned when a struct skips one of its fields while
+    /// [`Serializer::structs_as_arrays`](crate::Serializer::structs_as_arrays) is enabled, since
+    /// every following field would then be read into the wrong position.
+    SkippedField(&'static str),
+
+    #[error("Raw data cannot be written for elements of type {0}")]
+    /// Returned when a [`Writer`](crate::Writer) is asked to copy raw element data into an array
+    /// whose elements aren't fixed-size numbers or booleans.
+    NoRawData(ArrayKind),
+
+    #[error("Arrays of {0} cannot be begun without their elements")]
+    /// Returned when a [`Writer`](crate::Writer) is asked to begin a complex array, whose header
+    /// depends on the type of the parts of its elements.
+    NoArrayHeader(ArrayKind),
+
+    #[error("Non-canonical input: {0}")]
+    /// Returned by a [`Deserializer`](crate::Deserializer) in strict mode when the input is valid
+    /// BEVE but not in the canonical form written by
+    /// [`Serializer::canonical`](crate::Serializer::canonical).
+    NonCanonical(Violation),
+
     #[error("Invalid header: {0:08b}")]
     /// Returned when a header is encountered that does not fit the BEVE format.
     InvalidHeader(u8),
@@ -94,6 +135,29 @@ pub enum Error {
     /// Returned when attempting to deserialize an enum tag as something other than an identifier.
     InvalidTag,
 
+    #[error("Invalid variant `{variant}`: {source}")]
+    /// Returned when the payload of an enum variant written by name fails to deserialize.
+    InvalidVariant {
+        variant: String,
+        #[source]
+        source: Box<Error>,
+    },
+
+    #[error("At `{path}`: {source}")]
+    /// Wraps an error that occurred while serializing a value nested within arrays or objects.
+    ///
+    /// `path` leads to the value from the outermost one, naming struct fields and string keys and
+    /// indexing elements and integer keys in brackets, e.g. `config.routes[3].headers`. It is also
+    /// returned by [`Error::path`].
+    ///
+    /// Errors from nested values used to be returned unwrapped. Match on [`Error::without_path`]
+    /// to check which error occurred regardless of where.
+    AtPath {
+        path: String,
+        #[source]
+        source: Box<Error>,
+    },
+
     #[error("No character")]
     /// Returned when an empty string is attempted to be deserialized as a character.
     NoChar,
@@ -103,7 +167,8 @@ pub enum Error {
     InvalidComplexHeader,
 
     #[error("Invalid matrix member type")]
-    /// Returned when attempting to deserialize part of a matrix as an improper type.
+    /// Returned when the extents of a matrix are not an unsigned array, or its values are not a
+    /// numeric or complex array.
     InvalidMatrixType,
 }
 
@@ -113,7 +178,8 @@ pub enum SpecialType {
     HalfFloat,
     /// A [brain float](https://en.wikipedia.org/wiki/Bfloat16_floating-point_format).
     BrainFloat,
-    /// A 128-bit float.
+    /// A 128-bit float. These are read as [`F128Bits`](crate::F128Bits), so this is no longer
+    /// returned.
     F128,
 }
 
@@ -127,6 +193,91 @@ impl std::fmt::Display for SpecialType {
     }
 }
 
+/// A way in which input can deviate from the canonical form.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Violation {
+    /// A size was written with more bytes than its value requires.
+    OversizedSize,
+    /// The keys of an object are not in ascending order.
+    UnsortedKeys,
+    /// The same key appears more than once in an object.
+    DuplicateKey,
+    /// A NaN other than the canonical quiet NaN, or a negative zero.
+    NonCanonicalFloat,
+    /// An unused bit of the last byte of a boolean array is set.
+    BoolArrayPadding,
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Violation::OversizedSize => write!(f, "size prefix is longer than necessary"),
+            Violation::UnsortedKeys => write!(f, "object keys are not sorted"),
+            Violation::DuplicateKey => write!(f, "duplicate object key"),
+            Violation::NonCanonicalFloat => write!(f, "non-canonical NaN or negative zero"),
+            Violation::BoolArrayPadding => write!(f, "unused bits of a boolean array are set"),
+        }
+    }
+}
+
+impl Error {
+    /// Returns the path to the value that caused a serialization error, if it occurred within an
+    /// array or object.
+    ///
+    /// ```
+    /// use std::collections::BTreeMap;
+    ///
+    /// // Tuples can't be keys
+    /// let routes = vec![BTreeMap::new(), BTreeMap::from([((1, 2), "a")])];
+    /// let error = serde_beve::to_bytes(&routes).unwrap_err();
+    /// assert_eq!(error.path(), Some("[1]"));
+    /// ```
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            Self::AtPath { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Returns the error without the path it occurred at, if any.
+    pub fn without_path(&self) -> &Error {
+        match self {
+            Self::AtPath { source, .. } => source,
+            _ => self,
+        }
+    }
+
+    /// Records that the error occurred within the field or string key `name`.
+    pub(crate) fn in_field(self, name: &str) -> Self {
+        self.within(name.to_owned())
+    }
+
+    /// Records that the error occurred within the element or integer key `index`.
+    pub(crate) fn in_element(self, index: impl std::fmt::Display) -> Self {
+        self.within(format!("[{index}]"))
+    }
+
+    fn within(self, segment: String) -> Self {
+        match self {
+            Self::AtPath { path, source } => {
+                let path = if path.starts_with('[') {
+                    segment + &path
+                } else {
+                    segment + "." + &path
+                };
+                Self::AtPath { path, source }
+            }
+            // These are about the output rather than the value being serialized
+            Self::Io(..) | Self::BufferFull { .. } => self,
+            source => Self::AtPath {
+                path: segment,
+                source: Box::new(source),
+            },
+        }
+    }
+}
+
 impl serde::ser::Error for Error {
     fn custom<T>(msg: T) -> Self
     where
@@ -0,0 +1,165 @@
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{SeqAccess, Visitor},
+};
+use std::fmt;
+
+/// The name of the newtype struct an [`F128Bits`] serializes as, by which the
+/// [`Serializer`](crate::Serializer) recognizes it.
+pub(crate) const NAME: &str = "$serde_beve::F128Bits";
+
+/// The little-endian bytes of an IEEE 754 binary128 float.
+///
+/// Rust has no stable 128-bit float, so this type only carries them through as-is. Sequences of
+/// them are written as arrays of 128-bit floats. [`F128Bits::to_f64`] can be used to inspect
+/// their values.
+///
+/// Other formats see a tuple of the bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct F128Bits(pub [u8; 16]);
+
+const EXPONENT_BIAS: i32 = 16383;
+const MANTISSA_BITS: u32 = 112;
+
+impl F128Bits {
+    pub const fn to_bits(self) -> u128 {
+        u128::from_le_bytes(self.0)
+    }
+
+    pub const fn from_bits(bits: u128) -> Self {
+        Self(bits.to_le_bytes())
+    }
+
+    /// Converts to the nearest `f64`, rounding ties to even.
+    ///
+    /// Values too large for an `f64` become infinities, and values too small become zeros. NaNs
+    /// stay NaNs, keeping as much of their payload as fits.
+    pub fn to_f64(self) -> f64 {
+        let bits = self.to_bits();
+        let sign = ((bits >> 127) as u64) << 63;
+        let exponent = ((bits >> MANTISSA_BITS) & 0x7fff) as i32;
+        let mantissa = bits & ((1 << MANTISSA_BITS) - 1);
+
+        if exponent == 0x7fff {
+            return if mantissa == 0 {
+                f64::from_bits(sign | f64::INFINITY.to_bits())
+            } else {
+                // Quieted, as the payload may be cut down to nothing
+                let payload = (mantissa >> (MANTISSA_BITS - 52)) as u64 | 1 << 51;
+                f64::from_bits(sign | 0x7ff << 52 | payload)
+            };
+        }
+        // Subnormals are far smaller than the smallest `f64`
+        if exponent == 0 {
+            return f64::from_bits(sign);
+        }
+
+        let significand = mantissa | 1 << MANTISSA_BITS;
+        let exponent = exponent - EXPONENT_BIAS;
+        if exponent > 1023 {
+            return f64::from_bits(sign | f64::INFINITY.to_bits());
+        }
+
+        // Bits dropped from the significand, more if the result is subnormal
+        let shift = (MANTISSA_BITS - 52) as i32 + (-1022 - exponent).max(0);
+        if shift > MANTISSA_BITS as i32 + 1 {
+            return f64::from_bits(sign);
+        }
+        let shift = shift as u32;
+        let kept = (significand >> shift) as u64;
+        let rest = significand & ((1 << shift) - 1);
+        let half = 1 << (shift - 1);
+        let kept = if rest > half || (rest == half && kept & 1 == 1) {
+            kept + 1
+        } else {
+            kept
+        };
+
+        if exponent < -1022 {
+            // Rounding up into the smallest normal sets the exponent bit by itself
+            return f64::from_bits(sign | kept);
+        }
+        // The implicit bit of `kept` adds one to the exponent, and rounding up may carry into it,
+        // up to infinity
+        let exponent = ((exponent + 1022) as u64) << 52;
+        f64::from_bits(sign | (exponent + kept))
+    }
+
+    /// Converts from an `f64`, which is always exact.
+    pub fn from_f64(value: f64) -> Self {
+        let bits = value.to_bits();
+        let sign = ((bits >> 63) as u128) << 127;
+        let exponent = ((bits >> 52) & 0x7ff) as i32;
+        let mantissa = bits & ((1 << 52) - 1);
+
+        let (exponent, mantissa) = match exponent {
+            0x7ff => (0x7fff, mantissa),
+            0 if mantissa == 0 => (0, 0),
+            // Subnormal `f64`s are normal here
+            0 => {
+                let shift = mantissa.leading_zeros() - 11;
+                (
+                    -1022 - shift as i32 + EXPONENT_BIAS,
+                    (mantissa << shift) & ((1 << 52) - 1),
+                )
+            }
+            exponent => (exponent - 1023 + EXPONENT_BIAS, mantissa),
+        };
+        Self::from_bits(
+            sign | (exponent as u128) << MANTISSA_BITS | (mantissa as u128) << (MANTISSA_BITS - 52),
+        )
+    }
+}
+
+impl From<f64> for F128Bits {
+    fn from(value: f64) -> Self {
+        Self::from_f64(value)
+    }
+}
+
+impl Serialize for F128Bits {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(NAME, &self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for F128Bits {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_newtype_struct(NAME, F128Visitor)
+    }
+}
+
+struct F128Visitor;
+
+impl<'de> Visitor<'de> for F128Visitor {
+    type Value = F128Bits;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("the 16 bytes of a 128-bit float")
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        <[u8; 16]>::deserialize(deserializer).map(F128Bits)
+    }
+
+    fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        v.try_into()
+            .map(F128Bits)
+            .map_err(|_| E::invalid_length(v.len(), &self))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+        <[u8; 16]>::deserialize(serde::de::value::SeqAccessDeserializer::new(seq)).map(F128Bits)
+    }
+
+    fn visit_f64<E: serde::de::Error>(self, v: f64) -> Result<Self::Value, E> {
+        Ok(F128Bits::from_f64(v))
+    }
+
+    fn visit_f32<E: serde::de::Error>(self, v: f32) -> Result<Self::Value, E> {
+        Ok(F128Bits::from_f64(v as f64))
+    }
+}
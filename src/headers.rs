@@ -137,6 +137,7 @@ pub const fn header_name(header: u8) -> &'static str {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum ArrayKind {
     Generic,
     String,
@@ -155,6 +156,7 @@ pub enum ArrayKind {
     F16,
     F32,
     F64,
+    F128,
     Complex,
 }
 
@@ -178,9 +180,47 @@ impl ArrayKind {
             Self::F16 => F16,
             Self::F32 => F32,
             Self::F64 => F64,
+            Self::F128 => F128,
             Self::Complex => COMPLEX,
         }
     }
+
+    pub const fn array_header(self) -> u8 {
+        match self {
+            Self::Generic => GENERIC_ARRAY,
+            Self::String => STRING_ARRAY,
+            Self::Boolean => BOOL_ARRAY,
+            Self::I8 => I8_ARRAY,
+            Self::I16 => I16_ARRAY,
+            Self::I32 => I32_ARRAY,
+            Self::I64 => I64_ARRAY,
+            Self::I128 => I128_ARRAY,
+            Self::U8 => U8_ARRAY,
+            Self::U16 => U16_ARRAY,
+            Self::U32 => U32_ARRAY,
+            Self::U64 => U64_ARRAY,
+            Self::U128 => U128_ARRAY,
+            Self::BF16 => BF16_ARRAY,
+            Self::F16 => F16_ARRAY,
+            Self::F32 => F32_ARRAY,
+            Self::F64 => F64_ARRAY,
+            Self::F128 => F128_ARRAY,
+            Self::Complex => COMPLEX,
+        }
+    }
+
+    /// The size in bytes of each element of a typed array of this kind, or [`None`] if its
+    /// elements aren't stored as fixed-size values.
+    pub(crate) const fn element_size(self) -> Option<usize> {
+        match self {
+            Self::I8 | Self::U8 => Some(1),
+            Self::I16 | Self::U16 | Self::BF16 | Self::F16 => Some(2),
+            Self::I32 | Self::U32 | Self::F32 => Some(4),
+            Self::I64 | Self::U64 | Self::F64 => Some(8),
+            Self::I128 | Self::U128 | Self::F128 => Some(16),
+            Self::Generic | Self::String | Self::Boolean | Self::Complex => None,
+        }
+    }
 }
 
 impl std::fmt::Display for ArrayKind {
@@ -203,12 +243,14 @@ impl std::fmt::Display for ArrayKind {
             Self::F16 => write!(f, "{}", header_name(F16)),
             Self::F32 => write!(f, "{}", header_name(F32)),
             Self::F64 => write!(f, "{}", header_name(F64)),
+            Self::F128 => write!(f, "{}", header_name(F128)),
             Self::Complex => write!(f, "{}", header_name(COMPLEX)),
         }
     }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum ObjectKind {
     U8,
     U16,
@@ -259,7 +301,7 @@ impl std::fmt::Display for ObjectKind {
     }
 }
 
-const NUM_TYPE_MASK: u8 = 0b11111000;
+pub const NUM_TYPE_MASK: u8 = 0b11111000;
 
 // Just the bits representing the size of the number
 pub const I8_HEADER: u8 = I8 & NUM_TYPE_MASK;
@@ -4,12 +4,60 @@
 //!
 //! ## 16-bit floats
 //!
-//! BEVE supports both [`f16`](half::f16)s and [brain floats](https://en.wikipedia.org/wiki/Bfloat16_floating-point_format). This crate supports the deserialization of both, converting them into [`f32`]s. However, similar to [extension types](https://github.com/beve-org/beve/blob/main/extensions.md), Serde provides no means of serializing these types, and as such **their deserialization is gated by the `half` feature**, which enables the use of the [`half`] crate for working with them.
+//! BEVE supports both [`f16`](half::f16)s and [brain floats](https://en.wikipedia.org/wiki/Bfloat16_floating-point_format). Serde has no data types for them, so support for both is **gated by the `half` feature**, which enables the use of the [`half`] crate for working with them. With it, [`half::f16`] and [`half::bf16`] values (and sequences of them) are serialized with their own headers, recognized by the names of the newtype structs [`half`] serializes them as. They deserialize into the same types with their bits intact, NaN payloads and all, or into [`f32`]s and [`f64`]s.
+//!
+//! ## Boolean arrays
+//!
+//! Sequences of booleans are written as boolean arrays, packed eight to a byte starting from the most significant bit, as the reference implementation packs them. With the `bitvec` feature enabled, the [`bitvec`](mod@bitvec) module can be used with `#[serde(with)]` to serialize [`BitVec`](::bitvec::vec::BitVec)s and [`BitSlice`](::bitvec::slice::BitSlice)s the same way, copying their bits in bulk rather than one boolean at a time.
+//!
+//! Versions of this crate before 2.0.0 packed boolean arrays starting from the least significant bit instead, so their output reads back with its booleans reversed within each byte. Enable [`Deserializer::lsb_first_bool_arrays`] to read such data, and write it back out to migrate it.
+//!
+//! ## 128-bit floats
+//!
+//! Rust has no stable 128-bit float, so these are read and written through the opaque [`F128Bits`] type, which keeps their bytes as-is and can be converted to and from [`f64`]s.
+//!
+//! ## Complex numbers
+//!
+//! Serde has no data type for complex numbers either, so they are serialized through the [`Complex`] type, using BEVE's [complex extension](https://github.com/beve-org/beve/blob/main/extensions.md). Sequences of them are written as complex arrays. With the `num-complex` feature enabled, [`Complex`] converts to and from [`num_complex::Complex`](::num_complex::Complex), and the [`num_complex`] module can be used with `#[serde(with)]` to serialize the latter the same way.
+//!
+//! ## Matrices
+//!
+//! Likewise, multidimensional arrays are serialized through the [`Matrix`] type, using BEVE's matrix extension. Its values can be of any numeric type, including [`Complex`] numbers. Matrices can also be deserialized as maps, with the keys `layout`, `extents`, and `value`.
+//!
+//! ## Streaming
+//!
+//! By default, values are first converted into an intermediate [`Value`], since the header of an array or object depends on all of its contents. For very large data, this means holding a second copy of it in memory. [`to_writer_streaming`] and the [`StreamingSerializer`] instead write everything as soon as it is known, at the cost of requiring the lengths of sequences and maps up front and the elements of a sequence to agree in type with its first element. Its output reads back into the same values, but is not always byte-for-byte the same; see the [`StreamingSerializer`] for the differences.
+//!
+//! ## Writing by hand
+//!
+//! For code generators and hot loops, a [`Writer`] emits headers, objects, arrays, and typed array data directly, without needing types that implement `Serialize`. Typed arrays can also be streamed from iterators or from readers of raw little-endian data, so that even arrays too large to hold in memory can be written.
+//!
+//! ## Typed arrays
+//!
+//! Sequences of numbers are serialized one element at a time, like any other sequence. Wrapping a numeric slice in a [`TypedSlice`], or a vector in a [`TypedArray`], instead copies all of its elements at once, both when writing and when reading. A [`StreamingSerializer`] writes them straight from memory.
+//!
+//! ## Empty collections
+//!
+//! Serde doesn't say what type the elements of a sequence or the keys of a map are, so the headers of arrays and objects are decided by their contents. Empty sequences are therefore written as generic arrays and empty maps as string-keyed objects. Wrapping a collection in [`Typed`] writes it with the header of its element or key type even when it is empty, for readers that expect, say, an empty array of `f64`s.
+//!
+//! ## Compact numbers
+//!
+//! Numbers are written with the header of their Rust type by default. With [`Serializer::compact_numbers`] enabled, integers are instead written with the smallest header that can hold their value, and `f64`s that are exactly representable as `f32`s are written as such. The [`Deserializer`] accepts these narrower numbers wherever a wider one is expected.
+//!
+//! ## Canonical encoding
+//!
+//! The order of the fields of a map follows its iteration order, which for types like `HashMap` can differ between equal values. With [`Serializer::canonical`] enabled, object keys are sorted, NaNs and negative zeros are normalized, and equal values always produce identical bytes, making the output suitable for hashing or signing.
 //!
 //! ## Notes
 //!
 //! Since BEVE is a binary format, this crate doesn't provide any tools for serializing to or deserializing from strings.
 //!
+//! For the same reason, the format is not human-readable, so types like IP addresses use their compact binary representations rather than strings. This can be changed with [`Serializer::human_readable`] and its deserializer counterpart.
+//!
+//! Objects can only be keyed by strings or integers. Map keys of other types are written as one of the two where there is an obvious way to do so: characters as strings, unit enum variants as their names, and newtype structs as the key they wrap.
+//!
+//! Errors that occur while serializing a value nested within arrays or objects report where it is, such as `config.routes[3].headers`, through [`Error::path`].
+//!
 //! BEVE collections (arrays, object, and strings) store their lengths as compressed integers[^1]. The compression method uses the first two bits to indicate the number of bytes in the integer, and as such, the maximum size is 62 bits[^2]. If, for some reason, you have a string with more than that many bytes, an array with more than that many items, or (heaven forbid) a struct or map with more than that many fields, serialization will fail.
 //!
 //! BEVE is a little-endian format, and for the sake of simplicity, this crate assumes it is being used on a little-endian system.
@@ -27,10 +75,26 @@ pub mod ser;
 /// Intermediate representation of values used during serialization.
 pub mod value;
 
+mod bits;
+mod complex;
+mod f128;
 mod headers;
+mod matrix;
+mod typed;
 
+#[cfg(feature = "bitvec")]
+pub use bits::bitvec;
+pub use complex::Complex;
+#[cfg(feature = "num-complex")]
+pub use complex::num_complex;
 pub use de::{Deserializer, from_bytes, from_reader};
 pub use error::{Error, Result};
+pub use f128::F128Bits;
 pub use headers::{ArrayKind, ObjectKind};
-pub use ser::{Serializer, to_bytes, to_writer};
+pub use matrix::{Matrix, MatrixLayout};
+pub use ser::{
+    Number, Serializer, StreamingSerializer, Writer, serialized_size, to_bytes, to_slice,
+    to_writer, to_writer_streaming,
+};
+pub use typed::{Collection, Element, Key, Typed, TypedArray, TypedSlice};
 pub use value::Value;
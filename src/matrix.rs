@@ -0,0 +1,131 @@
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{Error as _, SeqAccess, Unexpected, Visitor},
+};
+use std::{fmt, marker::PhantomData};
+
+/// The name of the newtype struct a [`Matrix`] serializes as, by which the
+/// [`Serializer`](crate::Serializer) recognizes it.
+pub(crate) const NAME: &str = "$serde_beve::Matrix";
+
+/// The order in which the values of a [`Matrix`] are stored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum MatrixLayout {
+    /// Row-major: the last extent varies fastest.
+    #[default]
+    Right,
+    /// Column-major: the first extent varies fastest.
+    Left,
+}
+
+impl MatrixLayout {
+    /// The bit that identifies the layout in the matrix header.
+    pub(crate) const fn bit(self) -> u8 {
+        match self {
+            Self::Right => 0,
+            Self::Left => 1,
+        }
+    }
+
+    pub(crate) const fn from_bit(bit: u8) -> Self {
+        if bit & 1 == 1 {
+            Self::Left
+        } else {
+            Self::Right
+        }
+    }
+
+    pub(crate) const fn name(self) -> &'static str {
+        match self {
+            Self::Right => "layout_right",
+            Self::Left => "layout_left",
+        }
+    }
+}
+
+/// A multidimensional array, serialized using BEVE's
+/// [matrix extension](https://github.com/beve-org/beve/blob/main/extensions.md).
+///
+/// The values are stored flat, in the order given by the layout, and may be of any numeric type,
+/// including [`Complex`](crate::Complex) numbers.
+///
+/// Other formats see a tuple of the layout (`0` for [`MatrixLayout::Right`] and `1` for
+/// [`MatrixLayout::Left`]), the extents, and the values.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Matrix<T> {
+    pub layout: MatrixLayout,
+    pub extents: Vec<usize>,
+    pub values: Vec<T>,
+}
+
+impl<T> Matrix<T> {
+    /// Creates a matrix stored in column-major order.
+    pub fn layout_left(extents: Vec<usize>, values: Vec<T>) -> Self {
+        Self {
+            layout: MatrixLayout::Left,
+            extents,
+            values,
+        }
+    }
+
+    /// Creates a matrix stored in row-major order.
+    pub fn layout_right(extents: Vec<usize>, values: Vec<T>) -> Self {
+        Self {
+            layout: MatrixLayout::Right,
+            extents,
+            values,
+        }
+    }
+}
+
+impl<T: Serialize> Serialize for Matrix<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(NAME, &(self.layout.bit(), &self.extents, &self.values))
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Matrix<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_newtype_struct(NAME, MatrixVisitor(PhantomData))
+    }
+}
+
+struct MatrixVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for MatrixVisitor<T> {
+    type Value = Matrix<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a matrix")
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_tuple(3, self)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let layout: u8 = seq
+            .next_element()?
+            .ok_or_else(|| A::Error::invalid_length(0, &self))?;
+        if layout > 1 {
+            return Err(A::Error::invalid_value(
+                Unexpected::Unsigned(layout as u64),
+                &"0 or 1",
+            ));
+        }
+        let extents = seq
+            .next_element()?
+            .ok_or_else(|| A::Error::invalid_length(1, &self))?;
+        let values = seq
+            .next_element()?
+            .ok_or_else(|| A::Error::invalid_length(2, &self))?;
+        Ok(Matrix {
+            layout: MatrixLayout::from_bit(layout),
+            extents,
+            values,
+        })
+    }
+}
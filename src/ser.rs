@@ -1,15 +1,39 @@
+mod canonical;
+mod compact;
 mod map;
 mod seq;
+mod streaming;
+mod writer;
 
 pub use map::MapSerializer;
 pub use seq::SeqSerializer;
+pub use streaming::{
+    StreamingMapSerializer, StreamingSeqSerializer, StreamingSerializer, StreamingTupleSerializer,
+};
+pub use writer::{Number, Writer};
 
-use crate::{Value, error::Error, headers::*};
-use std::io::Write;
+use crate::{
+    Value,
+    error::Error,
+    headers::{ArrayKind, ObjectKind},
+    matrix::MatrixLayout,
+    typed::Hint,
+};
+use std::{borrow::Cow, io::Write};
 
 pub struct Serializer<W: Write> {
     writer: W,
     write: bool,
+    /// The kind of the [`TypedSlice`](crate::TypedSlice) whose bytes are being serialized.
+    typed_array: Option<ArrayKind>,
+    compact_numbers: bool,
+    promote_arrays: bool,
+    canonical: bool,
+    skip_none_fields: bool,
+    structs_as_arrays: bool,
+    struct_field_indices: bool,
+    variants_by_name: bool,
+    human_readable: bool,
 }
 
 impl<W: Write> Serializer<W> {
@@ -17,35 +41,447 @@ impl<W: Write> Serializer<W> {
         Self {
             writer,
             write: true,
+            typed_array: None,
+            compact_numbers: false,
+            promote_arrays: false,
+            canonical: false,
+            skip_none_fields: false,
+            structs_as_arrays: false,
+            struct_field_indices: false,
+            variants_by_name: false,
+            human_readable: false,
         }
     }
 
-    fn serialize_num_array<T: Copy, const N: usize>(
-        &mut self,
-        v: &[T],
-        f: fn(T) -> [u8; N],
-    ) -> Result<(), Error> {
+    /// Write every number with the smallest header that can hold it.
+    ///
+    /// Integers are narrowed to the smallest type of the same signedness that fits their value,
+    /// and `f64`s are written as `f32`s when the conversion is exact. Typed arrays are narrowed as
+    /// a whole, to the smallest type that fits every element. Integer object keys keep their
+    /// declared width.
+    ///
+    /// A [`Deserializer`](crate::Deserializer) reads narrowed numbers back into their original
+    /// types, but other implementations may not.
+    ///
+    /// When used with a [`StreamingSerializer`], typed arrays are written as soon as their first
+    /// element is known and are therefore never narrowed.
+    pub fn compact_numbers(mut self, compact: bool) -> Self {
+        self.compact_numbers = compact;
+        self
+    }
+
+    /// Write sequences of numbers that differ in width as typed arrays.
+    ///
+    /// By default, a sequence is only written as a typed array if all of its elements are of the
+    /// same type, and falls back to a generic array otherwise. With this enabled, a sequence whose
+    /// elements are all signed integers, all unsigned integers, or all floats is instead written
+    /// as a typed array of the widest type among them.
+    ///
+    /// This has no effect on a [`StreamingSerializer`], which decides the type of an array from its
+    /// first element.
+    pub fn promote_numeric_arrays(mut self, promote: bool) -> Self {
+        self.promote_arrays = promote;
+        self
+    }
+
+    /// Write a canonical encoding, so that equal values always produce identical bytes.
+    ///
+    /// In canonical mode:
+    ///
+    /// - The fields of every object are sorted by key. String keys are ordered by their bytes and
+    ///   integer keys by their value.
+    /// - Every NaN is written as the canonical quiet NaN, and negative zero as positive zero.
+    ///
+    /// Sizes are always written in their shortest form, so they need no special treatment.
+    ///
+    /// A [`StreamingSerializer`] writes every field as soon as it is given, so it cannot sort keys.
+    /// Its floats are still canonicalized.
+    pub fn canonical(mut self, canonical: bool) -> Self {
+        self.canonical = canonical;
+        self
+    }
+
+    /// Leave out struct fields whose value is `None`, instead of writing them as null.
+    ///
+    /// Deserializing a struct treats a missing optional field as `None`, so this round-trips
+    /// without any changes on the reading side. Maps are unaffected.
+    ///
+    /// A [`StreamingSerializer`] writes the number of fields before any of them, so it always
+    /// writes `None` fields.
+    pub fn skip_none_fields(mut self, skip: bool) -> Self {
+        self.skip_none_fields = skip;
+        self
+    }
+
+    /// Write structs as generic arrays of their field values, in declaration order, instead of as
+    /// objects keyed by field name.
+    ///
+    /// This leaves out the name of every field, which can make up most of the output for large
+    /// arrays of small structs. A [`Deserializer`](crate::Deserializer) reads these arrays back
+    /// into structs by position, so the fields of the struct must not be reordered, added, or
+    /// removed between writing and reading.
+    ///
+    /// Since every field is needed to keep the positions of the following ones,
+    /// [`skip_none_fields`](Self::skip_none_fields) has no effect on structs written this way, and
+    /// fields skipped by their `Serialize` implementation (e.g. with
+    /// `#[serde(skip_serializing_if)]`) fail with [`Error::SkippedField`].
+    pub fn structs_as_arrays(mut self, as_arrays: bool) -> Self {
+        self.structs_as_arrays = as_arrays;
+        self
+    }
+
+    /// Key the fields of structs by their index in declaration order instead of by name.
+    ///
+    /// Structs are written as `u8`-keyed objects, or `u16`-keyed objects if they have more than
+    /// 256 fields. Unlike [`structs_as_arrays`](Self::structs_as_arrays), fields can still be left
+    /// out (e.g. by [`skip_none_fields`](Self::skip_none_fields)), but fields must not be reordered
+    /// or removed between writing and reading. A [`Deserializer`](crate::Deserializer) maps the
+    /// indices back to field names.
+    ///
+    /// A [`StreamingSerializer`] picks the key type from the number of fields it is told about up
+    /// front, so a struct with more than 256 fields, some of which are skipped, may fail with
+    /// [`Error::TooLong`].
+    ///
+    /// This has no effect if [`structs_as_arrays`](Self::structs_as_arrays) is enabled.
+    pub fn struct_field_indices(mut self, indices: bool) -> Self {
+        self.struct_field_indices = indices;
+        self
+    }
+
+    /// Identify enum variants by name instead of by index.
+    ///
+    /// Unit variants are written as strings, and all other variants as objects with a single
+    /// field, keyed by the name of the variant, holding their payload. This keeps stored data
+    /// readable when the variants of an enum are reordered. A [`Deserializer`](crate::Deserializer)
+    /// reads variants in either form.
+    pub fn variants_by_name(mut self, by_name: bool) -> Self {
+        self.variants_by_name = by_name;
+        self
+    }
+
+    /// Report the format as human-readable to [`Serialize`](serde::Serialize) implementations.
+    ///
+    /// BEVE is a binary format, so types with both a textual and a compact representation (e.g.
+    /// IP addresses) are written in their compact form by default. Enable this to write them as
+    /// text instead, as versions of this crate before the option existed did.
+    pub fn human_readable(mut self, human_readable: bool) -> Self {
+        self.human_readable = human_readable;
+        self
+    }
+
+    /// Returns the number of bytes the `value` would be serialized into with these options,
+    /// without producing any output.
+    ///
+    /// This counts the output of a [`StreamingSerializer`] with these options, so the `value` is
+    /// never built up in memory. See [`StreamingSerializer`] for its restrictions, and for where
+    /// its output differs from that of this serializer.
+    pub fn serialized_size(&self, value: &impl serde::Serialize) -> Result<u64, Error> {
+        let mut counter = ByteCounter(0);
+        value.serialize(&mut StreamingSerializer::from(
+            self.with_writer(&mut counter),
+        ))?;
+        Ok(counter.0)
+    }
+
+    /// Returns a serializer with the same options, writing into the `writer`.
+    fn with_writer<V: Write>(&self, writer: V) -> Serializer<V> {
+        Serializer {
+            writer,
+            write: true,
+            typed_array: None,
+            compact_numbers: self.compact_numbers,
+            promote_arrays: self.promote_arrays,
+            canonical: self.canonical,
+            skip_none_fields: self.skip_none_fields,
+            structs_as_arrays: self.structs_as_arrays,
+            struct_field_indices: self.struct_field_indices,
+            variants_by_name: self.variants_by_name,
+            human_readable: self.human_readable,
+        }
+    }
+
+    /// Serializes the contents of a [`Complex`](crate::Complex) without writing or normalizing
+    /// them.
+    ///
+    /// Returns [`Value::Complex`] if its parts form a numeric array, or the contents as-is
+    /// otherwise.
+    fn serialize_complex<T>(&mut self, value: &T) -> Result<Value, Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        let write = std::mem::replace(&mut self.write, false);
+        // Narrowing is left to the caller, as the parts of complex arrays must agree in type
+        let compact = std::mem::replace(&mut self.compact_numbers, false);
+        let parts = value.serialize(&mut *self);
+        self.write = write;
+        self.compact_numbers = compact;
+
+        let parts = parts?;
+        Ok(if is_num_array(&parts) {
+            Value::Complex(Box::new(parts))
+        } else {
+            parts
+        })
+    }
+
+    /// Serializes the contents of a [`Matrix`](crate::Matrix) without writing them.
+    ///
+    /// Returns [`Value::Matrix`] if its extents form an unsigned array and its values a numeric or
+    /// complex array, or the contents as-is otherwise.
+    fn serialize_matrix<T>(&mut self, value: &T) -> Result<Value, Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        let write = std::mem::replace(&mut self.write, false);
+        let contents = value.serialize(&mut *self);
+        self.write = write;
+
+        let mut contents = match contents? {
+            Value::GenericArray(contents) if contents.len() == 3 => contents,
+            contents => return Ok(contents),
+        };
+        let layout = match contents[0] {
+            Value::U8(bit @ (0 | 1)) => MatrixLayout::from_bit(bit),
+            _ => return Ok(Value::GenericArray(contents)),
+        };
+        // Empty sequences don't know their element type
+        if matches!(&contents[1], Value::GenericArray(extents) if extents.is_empty()) {
+            contents[1] = Value::U64Array(Vec::new());
+        }
+        let extents_valid = matches!(
+            contents[1],
+            Value::U8Array(..)
+                | Value::U16Array(..)
+                | Value::U32Array(..)
+                | Value::U64Array(..)
+                | Value::U128Array(..)
+        );
+        if !extents_valid || !is_matrix_values(&contents[2]) {
+            return Ok(Value::GenericArray(contents));
+        }
+
+        let values = contents.pop().unwrap();
+        let extents = contents.pop().unwrap();
+        Ok(Value::Matrix(layout, Box::new(extents), Box::new(values)))
+    }
+
+    /// Serializes the contents of an [`F128Bits`](crate::F128Bits) without writing them.
+    ///
+    /// Returns [`Value::F128`] if they are 16 bytes, or the contents as-is otherwise.
+    fn serialize_f128<T>(&mut self, value: &T) -> Result<Value, Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        let write = std::mem::replace(&mut self.write, false);
+        let bytes = value.serialize(&mut *self);
+        self.write = write;
+
+        Ok(match bytes? {
+            Value::U8Array(bytes) => match <[u8; 16]>::try_from(bytes.as_ref()) {
+                Ok(bytes) => Value::F128(bytes),
+                Err(_) => Value::U8Array(bytes),
+            },
+            bytes => bytes,
+        })
+    }
+
+    /// Serializes the contents of a bit sequence from [`bitvec`](crate::bits) without writing
+    /// them.
+    ///
+    /// Returns [`Value::BoolArray`] if they are a length followed by just enough bytes to hold that
+    /// many bits, or the contents as-is otherwise.
+    fn serialize_bool_array<T>(&mut self, value: &T) -> Result<Value, Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        let write = std::mem::replace(&mut self.write, false);
+        let compact = std::mem::replace(&mut self.compact_numbers, false);
+        let contents = value.serialize(&mut *self);
+        self.write = write;
+        self.compact_numbers = compact;
+
+        let mut contents = match contents? {
+            Value::GenericArray(contents) if contents.len() == 2 => contents,
+            contents => return Ok(contents),
+        };
+        let len = match (&contents[0], &contents[1]) {
+            (Value::U64(len), Value::U8Array(bytes))
+                if usize::try_from(*len).is_ok_and(|len| len.div_ceil(8) == bytes.len()) =>
+            {
+                *len as usize
+            }
+            _ => return Ok(Value::GenericArray(contents)),
+        };
+        match contents.pop() {
+            Some(Value::U8Array(bytes)) => Ok(Value::BoolArray(len, bytes.into_owned())),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Serializes the contents of a [`TypedSlice`](crate::TypedSlice) of `kind` without writing
+    /// or normalizing them.
+    ///
+    /// Returns a numeric array if they are the bytes of a whole number of elements, or the contents
+    /// as-is otherwise.
+    fn serialize_typed_array<T>(&mut self, kind: ArrayKind, value: &T) -> Result<Value, Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        let write = std::mem::replace(&mut self.write, false);
+        // The bytes are copied into the array directly
+        self.typed_array = Some(kind);
+        let out = value.serialize(&mut *self);
+        self.typed_array = None;
+        self.write = write;
+        out
+    }
+
+    /// Serializes the contents of a [`Typed`](crate::Typed) collection without writing them.
+    ///
+    /// Returns an empty array or object with the header `hint` calls for if the collection is
+    /// empty, or the contents as-is otherwise.
+    fn serialize_typed<T>(&mut self, hint: Hint, value: &T) -> Result<Value, Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        let write = std::mem::replace(&mut self.write, false);
+        let contents = value.serialize(&mut *self);
+        self.write = write;
+
+        Ok(match (hint, contents?) {
+            (Hint::Array(kind), Value::GenericArray(elements)) if elements.is_empty() => {
+                empty_array(kind).unwrap_or(Value::GenericArray(elements))
+            }
+            (Hint::Object(kind), Value::StringObject(fields)) if fields.is_empty() => {
+                empty_object(kind)
+            }
+            (_, contents) => contents,
+        })
+    }
+
+    /// Serializes a [`half::f16`] or [`half::bf16`] without writing it.
+    ///
+    /// Both types serialize as newtype structs named after themselves, holding their bits as a
+    /// `u16`. Returns `None` without serializing anything if `name` is not one of theirs. If the
+    /// contents turn out not to be a `u16`, they are returned as-is. The float is canonicalized if
+    /// [`canonical`](Self::canonical) is enabled.
+    #[cfg(feature = "half")]
+    fn serialize_half<T>(&mut self, name: &'static str, value: &T) -> Result<Option<Value>, Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        if name != "f16" && name != "bf16" {
+            return Ok(None);
+        }
+
+        let write = std::mem::replace(&mut self.write, false);
+        let inner = value.serialize(&mut *self);
+        self.write = write;
+
+        let bits = match inner? {
+            Value::U16(bits) => bits,
+            // Narrowed by `compact_numbers`
+            Value::U8(bits) => bits as u16,
+            other => return Ok(Some(other)),
+        };
+        let out = if name == "f16" {
+            Value::F16(half::f16::from_bits(bits))
+        } else {
+            Value::BF16(half::bf16::from_bits(bits))
+        };
+        Ok(Some(if self.canonical {
+            canonical::canonicalize_floats(out)
+        } else {
+            out
+        }))
+    }
+
+    /// Wraps the payload of an enum variant in its tag, or in an object keyed by its name if
+    /// [`variants_by_name`](Self::variants_by_name) is enabled.
+    fn wrap_variant(&self, variant_index: u32, variant: &'static str, value: Value) -> Value {
+        if self.variants_by_name {
+            Value::StringObject(vec![(Cow::Borrowed(variant.as_bytes()), value)])
+        } else {
+            Value::Tag(variant_index as usize, Box::new(value))
+        }
+    }
+
+    /// Applies the canonical float policy and number narrowing to `value`, if enabled.
+    fn normalize(&self, value: Value) -> Value {
+        // The parts of complex numbers are narrowed together
+        let value = match value {
+            Value::Complex(parts) => return Value::Complex(Box::new(self.normalize(*parts))),
+            Value::ComplexArray(parts) => {
+                return Value::ComplexArray(Box::new(self.normalize(*parts)));
+            }
+            value => value,
+        };
+        let value = if self.canonical {
+            canonical::canonicalize_floats(value)
+        } else {
+            value
+        };
+        if self.compact_numbers {
+            compact::narrow(value)
+        } else {
+            value
+        }
+    }
+
+    fn serialize_number(&mut self, value: Value) -> Result<Value, Error> {
+        let out = self.normalize(value);
+        if self.write {
+            self.serialize_value(&out)?;
+        }
+        Ok(out)
+    }
+
+    fn serialize_num_array<T: bytemuck::Pod>(&mut self, v: &[T]) -> Result<(), Error> {
         self.serialize_size(v.len())?;
-        let bytes = v.iter().flat_map(|v| f(*v)).collect::<Vec<_>>();
-        self.writer.write_all(&bytes)?;
+        self.serialize_num_elements(v)
+    }
+
+    fn serialize_num_elements<T: bytemuck::Pod>(&mut self, v: &[T]) -> Result<(), Error> {
+        // Numbers are stored little-endian, like the platform's own
+        self.writer.write_all(bytemuck::cast_slice(v))?;
         Ok(())
     }
 
-    fn serialize_size(&mut self, mut size: usize) -> Result<(), Error> {
+    /// Writes the elements of a numeric array, without its header or size.
+    fn serialize_num_data(&mut self, value: &Value) -> Result<(), Error> {
+        match value {
+            Value::F32Array(v) => self.serialize_num_elements(v),
+            Value::F64Array(v) => self.serialize_num_elements(v),
+            Value::I8Array(v) => self.serialize_num_elements(v),
+            Value::I16Array(v) => self.serialize_num_elements(v),
+            Value::I32Array(v) => self.serialize_num_elements(v),
+            Value::I64Array(v) => self.serialize_num_elements(v),
+            Value::I128Array(v) => self.serialize_num_elements(v),
+            Value::U8Array(v) => Ok(self.writer.write_all(v)?),
+            Value::U16Array(v) => self.serialize_num_elements(v),
+            Value::U32Array(v) => self.serialize_num_elements(v),
+            Value::U64Array(v) => self.serialize_num_elements(v),
+            Value::U128Array(v) => self.serialize_num_elements(v),
+            _ => unreachable!(),
+        }
+    }
+
+    fn serialize_size(&mut self, size: usize) -> Result<(), Error> {
         if size >= 2_usize.pow(62) {
             return Err(Error::TooLong);
         }
 
-        size <<= 2;
-
         if size < 2_usize.pow(6) {
-            self.writer.write_all(&[size as u8])?;
+            self.writer.write_all(&[(size << 2) as u8])?;
         } else if size < 2_usize.pow(14) {
-            self.writer.write_all(&(size as u16 | 1).to_le_bytes())?;
+            self.writer
+                .write_all(&((size << 2) as u16 | 1).to_le_bytes())?;
         } else if size < 2_usize.pow(30) {
-            self.writer.write_all(&(size as u32 | 2).to_le_bytes())?;
+            self.writer
+                .write_all(&((size << 2) as u32 | 2).to_le_bytes())?;
         } else {
-            self.writer.write_all(&(size as u64 | 3).to_le_bytes())?;
+            self.writer
+                .write_all(&((size << 2) as u64 | 3).to_le_bytes())?;
         }
 
         Ok(())
@@ -90,35 +526,35 @@ impl<W: Write> Serializer<W> {
             Value::I8Object(v) => {
                 self.serialize_size(v.len())?;
                 for (k, v) in v {
-                    self.serialize_value(&Value::I8(*k))?;
+                    self.writer.write_all(&k.to_le_bytes())?;
                     self.serialize_value(v)?;
                 }
             }
             Value::I16Object(v) => {
                 self.serialize_size(v.len())?;
                 for (k, v) in v {
-                    self.serialize_value(&Value::I16(*k))?;
+                    self.writer.write_all(&k.to_le_bytes())?;
                     self.serialize_value(v)?;
                 }
             }
             Value::I32Object(v) => {
                 self.serialize_size(v.len())?;
                 for (k, v) in v {
-                    self.serialize_value(&Value::I32(*k))?;
+                    self.writer.write_all(&k.to_le_bytes())?;
                     self.serialize_value(v)?;
                 }
             }
             Value::I64Object(v) => {
                 self.serialize_size(v.len())?;
                 for (k, v) in v {
-                    self.serialize_value(&Value::I64(*k))?;
+                    self.writer.write_all(&k.to_le_bytes())?;
                     self.serialize_value(v)?;
                 }
             }
             Value::I128Object(v) => {
                 self.serialize_size(v.len())?;
                 for (k, v) in v {
-                    self.serialize_value(&Value::I128(*k))?;
+                    self.writer.write_all(&k.to_le_bytes())?;
                     self.serialize_value(v)?;
                 }
             }
@@ -126,76 +562,81 @@ impl<W: Write> Serializer<W> {
             Value::U8Object(v) => {
                 self.serialize_size(v.len())?;
                 for (k, v) in v {
-                    self.serialize_value(&Value::U8(*k))?;
+                    self.writer.write_all(&k.to_le_bytes())?;
                     self.serialize_value(v)?;
                 }
             }
             Value::U16Object(v) => {
                 self.serialize_size(v.len())?;
                 for (k, v) in v {
-                    self.serialize_value(&Value::U16(*k))?;
+                    self.writer.write_all(&k.to_le_bytes())?;
                     self.serialize_value(v)?;
                 }
             }
             Value::U32Object(v) => {
                 self.serialize_size(v.len())?;
                 for (k, v) in v {
-                    self.serialize_value(&Value::U32(*k))?;
+                    self.writer.write_all(&k.to_le_bytes())?;
                     self.serialize_value(v)?;
                 }
             }
             Value::U64Object(v) => {
                 self.serialize_size(v.len())?;
                 for (k, v) in v {
-                    self.serialize_value(&Value::U64(*k))?;
+                    self.writer.write_all(&k.to_le_bytes())?;
                     self.serialize_value(v)?;
                 }
             }
             Value::U128Object(v) => {
                 self.serialize_size(v.len())?;
                 for (k, v) in v {
-                    self.serialize_value(&Value::U128(*k))?;
+                    self.writer.write_all(&k.to_le_bytes())?;
                     self.serialize_value(v)?;
                 }
             }
 
+            Value::F128(v) => self.writer.write_all(v)?,
+            Value::F128Array(v) => {
+                self.serialize_size(v.len())?;
+                self.writer.write_all(v.as_flattened())?;
+            }
             Value::F32Array(v) => {
-                self.serialize_num_array(v, f32::to_le_bytes)?;
+                self.serialize_num_array(v)?;
             }
             Value::F64Array(v) => {
-                self.serialize_num_array(v, f64::to_le_bytes)?;
+                self.serialize_num_array(v)?;
             }
 
             Value::I8Array(v) => {
-                self.serialize_num_array(v, i8::to_le_bytes)?;
+                self.serialize_num_array(v)?;
             }
             Value::I16Array(v) => {
-                self.serialize_num_array(v, i16::to_le_bytes)?;
+                self.serialize_num_array(v)?;
             }
             Value::I32Array(v) => {
-                self.serialize_num_array(v, i32::to_le_bytes)?;
+                self.serialize_num_array(v)?;
             }
             Value::I64Array(v) => {
-                self.serialize_num_array(v, i64::to_le_bytes)?;
+                self.serialize_num_array(v)?;
             }
             Value::I128Array(v) => {
-                self.serialize_num_array(v, i128::to_le_bytes)?;
+                self.serialize_num_array(v)?;
             }
 
             Value::U8Array(v) => {
-                self.serialize_num_array(v, u8::to_le_bytes)?;
+                self.serialize_num_array(v)?;
             }
             Value::U16Array(v) => {
-                self.serialize_num_array(v, u16::to_le_bytes)?;
+                self.serialize_num_array(v)?;
             }
             Value::U32Array(v) => {
-                self.serialize_num_array(v, u32::to_le_bytes)?;
+                self.serialize_num_array(v)?;
             }
             Value::U64Array(v) => {
-                self.serialize_num_array(v, u64::to_le_bytes)?;
+                self.serialize_num_array(v)?;
             }
             Value::U128Array(v) => {
-                self.serialize_num_array(v, u128::to_le_bytes)?;
+                self.serialize_num_array(v)?;
             }
 
             Value::BoolArray(len, v) => {
@@ -220,35 +661,160 @@ impl<W: Write> Serializer<W> {
                 self.serialize_value(v)?;
             }
 
-            // Never serialized
+            Value::Matrix(layout, extents, values) => {
+                self.writer.write_all(&[layout.bit()])?;
+                self.serialize_value(extents)?;
+                self.serialize_value(values)?;
+            }
+            Value::Complex(parts) => {
+                self.writer.write_all(&[complex_header(parts)])?;
+                self.serialize_num_data(parts)?;
+            }
+            Value::ComplexArray(parts) => {
+                self.writer.write_all(&[complex_header(parts) | 1])?;
+                self.serialize_size(num_array_len(parts) / 2)?;
+                self.serialize_num_data(parts)?;
+            }
+
             #[cfg(feature = "half")]
-            Value::BF16(..) => unreachable!(),
-            #[cfg(not(feature = "half"))]
-            Value::BF16 => unreachable!(),
+            Value::BF16(v) => self.writer.write_all(&v.to_le_bytes())?,
             #[cfg(feature = "half")]
-            Value::BF16Array(..) => unreachable!(),
-            #[cfg(not(feature = "half"))]
-            Value::BF16Array => unreachable!(),
+            Value::F16(v) => self.writer.write_all(&v.to_le_bytes())?,
             #[cfg(feature = "half")]
-            Value::F16(..) => unreachable!(),
-            #[cfg(not(feature = "half"))]
-            Value::F16 => unreachable!(),
+            Value::BF16Array(v) => {
+                self.serialize_num_array(v)?;
+            }
             #[cfg(feature = "half")]
-            Value::F16Array(..) => unreachable!(),
+            Value::F16Array(v) => {
+                self.serialize_num_array(v)?;
+            }
+            #[cfg(not(feature = "half"))]
+            Value::BF16 | Value::BF16Array => {
+                return Err(Error::UnsupportedDataType(
+                    crate::error::SpecialType::BrainFloat,
+                ));
+            }
             #[cfg(not(feature = "half"))]
-            Value::F16Array => unreachable!(),
-            Value::F128
-            | Value::F128Array
-            | Value::Reserved
-            | Value::Complex
-            | Value::Matrix
-            | Value::Delimiter => unreachable!(),
+            Value::F16 | Value::F16Array => {
+                return Err(Error::UnsupportedDataType(
+                    crate::error::SpecialType::HalfFloat,
+                ));
+            }
+
+            // Never serialized
+            Value::Reserved | Value::Delimiter => {
+                unreachable!()
+            }
         }
 
         Ok(())
     }
 }
 
+fn is_num_array(value: &Value) -> bool {
+    matches!(
+        value,
+        Value::F32Array(..)
+            | Value::F64Array(..)
+            | Value::I8Array(..)
+            | Value::I16Array(..)
+            | Value::I32Array(..)
+            | Value::I64Array(..)
+            | Value::I128Array(..)
+            | Value::U8Array(..)
+            | Value::U16Array(..)
+            | Value::U32Array(..)
+            | Value::U64Array(..)
+            | Value::U128Array(..)
+    )
+}
+
+/// Reads a numeric array of `kind` from the bytes of its elements, or returns [`None`] if they
+/// don't make up a whole number of elements.
+fn typed_array(kind: ArrayKind, bytes: &[u8]) -> Option<Value> {
+    if bytes.len() % kind.element_size()? != 0 {
+        return None;
+    }
+    Some(match kind {
+        ArrayKind::I8 => Value::I8Array(bytemuck::pod_collect_to_vec(bytes)),
+        ArrayKind::I16 => Value::I16Array(bytemuck::pod_collect_to_vec(bytes)),
+        ArrayKind::I32 => Value::I32Array(bytemuck::pod_collect_to_vec(bytes)),
+        ArrayKind::I64 => Value::I64Array(bytemuck::pod_collect_to_vec(bytes)),
+        ArrayKind::I128 => Value::I128Array(bytemuck::pod_collect_to_vec(bytes)),
+        ArrayKind::U8 => Value::U8Array(Cow::Owned(bytes.to_vec())),
+        ArrayKind::U16 => Value::U16Array(bytemuck::pod_collect_to_vec(bytes)),
+        ArrayKind::U32 => Value::U32Array(bytemuck::pod_collect_to_vec(bytes)),
+        ArrayKind::U64 => Value::U64Array(bytemuck::pod_collect_to_vec(bytes)),
+        ArrayKind::U128 => Value::U128Array(bytemuck::pod_collect_to_vec(bytes)),
+        #[cfg(feature = "half")]
+        ArrayKind::BF16 => Value::BF16Array(bytemuck::pod_collect_to_vec(bytes)),
+        #[cfg(feature = "half")]
+        ArrayKind::F16 => Value::F16Array(bytemuck::pod_collect_to_vec(bytes)),
+        ArrayKind::F32 => Value::F32Array(bytemuck::pod_collect_to_vec(bytes)),
+        ArrayKind::F64 => Value::F64Array(bytemuck::pod_collect_to_vec(bytes)),
+        _ => return None,
+    })
+}
+
+/// Returns an empty array of `kind`, or [`None`] if there is no such array.
+fn empty_array(kind: ArrayKind) -> Option<Value> {
+    Some(match kind {
+        ArrayKind::F128 => Value::F128Array(Vec::new()),
+        ArrayKind::Boolean => Value::BoolArray(0, Vec::new()),
+        ArrayKind::String => Value::StringArray(Vec::new()),
+        kind => return typed_array(kind, &[]),
+    })
+}
+
+/// Returns an empty object keyed by `kind`.
+fn empty_object(kind: ObjectKind) -> Value {
+    match kind {
+        ObjectKind::U8 => Value::U8Object(Vec::new()),
+        ObjectKind::U16 => Value::U16Object(Vec::new()),
+        ObjectKind::U32 => Value::U32Object(Vec::new()),
+        ObjectKind::U64 => Value::U64Object(Vec::new()),
+        ObjectKind::U128 => Value::U128Object(Vec::new()),
+        ObjectKind::I8 => Value::I8Object(Vec::new()),
+        ObjectKind::I16 => Value::I16Object(Vec::new()),
+        ObjectKind::I32 => Value::I32Object(Vec::new()),
+        ObjectKind::I64 => Value::I64Object(Vec::new()),
+        ObjectKind::I128 => Value::I128Object(Vec::new()),
+        ObjectKind::String => Value::StringObject(Vec::new()),
+    }
+}
+
+fn is_matrix_values(value: &Value) -> bool {
+    #[cfg(feature = "half")]
+    if matches!(value, Value::BF16Array(..) | Value::F16Array(..)) {
+        return true;
+    }
+    is_num_array(value) || matches!(value, Value::F128Array(..) | Value::ComplexArray(..))
+}
+
+fn num_array_len(value: &Value) -> usize {
+    match value {
+        Value::F32Array(v) => v.len(),
+        Value::F64Array(v) => v.len(),
+        Value::I8Array(v) => v.len(),
+        Value::I16Array(v) => v.len(),
+        Value::I32Array(v) => v.len(),
+        Value::I64Array(v) => v.len(),
+        Value::I128Array(v) => v.len(),
+        Value::U8Array(v) => v.len(),
+        Value::U16Array(v) => v.len(),
+        Value::U32Array(v) => v.len(),
+        Value::U64Array(v) => v.len(),
+        Value::U128Array(v) => v.len(),
+        _ => unreachable!(),
+    }
+}
+
+/// The header following [`COMPLEX`](crate::headers::COMPLEX) for complex numbers with the given
+/// parts. Arrays additionally set the lowest bit.
+fn complex_header(parts: &Value) -> u8 {
+    parts.header() & crate::headers::NUM_TYPE_MASK
+}
+
 impl<'a, W: Write> serde::Serializer for &'a mut Serializer<W> {
     type Ok = Value;
     type Error = Error;
@@ -261,6 +827,11 @@ impl<'a, W: Write> serde::Serializer for &'a mut Serializer<W> {
     type SerializeStruct = MapSerializer<'a, W>;
     type SerializeStructVariant = MapSerializer<'a, W>;
 
+    fn is_human_readable(&self) -> bool {
+        // Typed arrays are written as their bytes either way
+        self.human_readable && self.typed_array.is_none()
+    }
+
     fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
         let out = if v { Value::True } else { Value::False };
         if self.write {
@@ -270,104 +841,56 @@ impl<'a, W: Write> serde::Serializer for &'a mut Serializer<W> {
     }
 
     fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
-        let out = Value::I8(v);
-        if self.write {
-            self.serialize_value(&out)?;
-        }
-        Ok(out)
+        self.serialize_number(Value::I8(v))
     }
 
     fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
-        let out = Value::I16(v);
-        if self.write {
-            self.serialize_value(&out)?;
-        }
-        Ok(out)
+        self.serialize_number(Value::I16(v))
     }
 
     fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
-        let out = Value::I32(v);
-        if self.write {
-            self.serialize_value(&out)?;
-        }
-        Ok(out)
+        self.serialize_number(Value::I32(v))
     }
 
     fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
-        let out = Value::I64(v);
-        if self.write {
-            self.serialize_value(&out)?;
-        }
-        Ok(out)
+        self.serialize_number(Value::I64(v))
     }
     fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
-        let out = Value::I128(v);
-        if self.write {
-            self.serialize_value(&out)?;
-        }
-        Ok(out)
+        self.serialize_number(Value::I128(v))
     }
 
     fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
-        let out = Value::U8(v);
-        if self.write {
-            self.serialize_value(&out)?;
-        }
-        Ok(out)
+        self.serialize_number(Value::U8(v))
     }
     fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
-        let out = Value::U16(v);
-        if self.write {
-            self.serialize_value(&out)?;
-        }
-        Ok(out)
+        self.serialize_number(Value::U16(v))
     }
     fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
-        let out = Value::U32(v);
-        if self.write {
-            self.serialize_value(&out)?;
-        }
-        Ok(out)
+        self.serialize_number(Value::U32(v))
     }
 
     fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
-        let out = Value::U64(v);
-        if self.write {
-            self.serialize_value(&out)?;
-        }
-        Ok(out)
+        self.serialize_number(Value::U64(v))
     }
 
     fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
-        let out = Value::U128(v);
-        if self.write {
-            self.serialize_value(&out)?;
-        }
-        Ok(out)
+        self.serialize_number(Value::U128(v))
     }
 
     fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
-        let out = Value::F32(v);
-        if self.write {
-            self.serialize_value(&out)?;
-        }
-        Ok(out)
+        self.serialize_number(Value::F32(v))
     }
 
     fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
-        let out = Value::F64(v);
-        if self.write {
-            self.serialize_value(&out)?;
-        }
-        Ok(out)
+        self.serialize_number(Value::F64(v))
     }
 
     fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
-        self.serialize_str(&v.to_string())
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
     }
 
     fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
-        let out = Value::String(v.bytes().collect());
+        let out = Value::String(Cow::Owned(v.as_bytes().to_vec()));
         if self.write {
             self.serialize_value(&out)?;
         }
@@ -375,8 +898,13 @@ impl<'a, W: Write> serde::Serializer for &'a mut Serializer<W> {
     }
 
     fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
-        // TODO: avoid copying?
-        let out = Value::U8Array(v.to_vec());
+        // Serde doesn't tie the lifetime of `v` to anything, so it can't be borrowed. Use a
+        // `StreamingSerializer` to avoid the copy.
+        let out = self
+            .typed_array
+            .take()
+            .and_then(|kind| typed_array(kind, v))
+            .unwrap_or_else(|| Value::U8Array(Cow::Owned(v.to_vec())));
         if self.write {
             self.serialize_value(&out)?;
         }
@@ -409,9 +937,13 @@ impl<'a, W: Write> serde::Serializer for &'a mut Serializer<W> {
         self,
         _name: &'static str,
         variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
     ) -> Result<Self::Ok, Self::Error> {
-        let out = Value::Tag(variant_index as usize, Box::new(Value::Null));
+        let out = if self.variants_by_name {
+            Value::String(Cow::Borrowed(variant.as_bytes()))
+        } else {
+            Value::Tag(variant_index as usize, Box::new(Value::Null))
+        };
         if self.write {
             self.serialize_value(&out)?;
         }
@@ -420,12 +952,69 @@ impl<'a, W: Write> serde::Serializer for &'a mut Serializer<W> {
 
     fn serialize_newtype_struct<T>(
         self,
-        _name: &'static str,
+        name: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
         T: ?Sized + serde::Serialize,
     {
+        if name == crate::f128::NAME {
+            let out = self.serialize_f128(value)?;
+            if self.write {
+                self.serialize_value(&out)?;
+            }
+            return Ok(out);
+        }
+        if name == crate::matrix::NAME {
+            let out = self.serialize_matrix(value)?;
+            if self.write {
+                self.serialize_value(&out)?;
+            }
+            return Ok(out);
+        }
+        if name == crate::bits::NAME {
+            let out = self.serialize_bool_array(value)?;
+            if self.write {
+                self.serialize_value(&out)?;
+            }
+            return Ok(out);
+        }
+        if let Some(kind) = crate::typed::kind(name) {
+            let out = self.serialize_typed_array(kind, value)?;
+            // Never narrowed, so that it can be read back as the same type
+            let out = if self.canonical {
+                canonical::canonicalize_floats(out)
+            } else {
+                out
+            };
+            if self.write {
+                self.serialize_value(&out)?;
+            }
+            return Ok(out);
+        }
+        if let Some(hint) = crate::typed::hint(name) {
+            let out = self.serialize_typed(hint, value)?;
+            if self.write {
+                self.serialize_value(&out)?;
+            }
+            return Ok(out);
+        }
+        if name == crate::complex::NAME {
+            let out = self.serialize_complex(value)?;
+            let out = self.normalize(out);
+            if self.write {
+                self.serialize_value(&out)?;
+            }
+            return Ok(out);
+        }
+        #[cfg(feature = "half")]
+        if let Some(out) = self.serialize_half(name, value)? {
+            if self.write {
+                self.serialize_value(&out)?;
+            }
+            return Ok(out);
+        }
+
         value.serialize(self)
     }
 
@@ -433,16 +1022,18 @@ impl<'a, W: Write> serde::Serializer for &'a mut Serializer<W> {
         self,
         _name: &'static str,
         variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
         T: ?Sized + serde::Serialize,
     {
-        let out = Value::Tag(
-            variant_index as usize,
-            Box::new(value.serialize(&mut *self)?),
-        );
+        // The value must only be written as part of the variant
+        let write = std::mem::replace(&mut self.write, false);
+        let value = value.serialize(&mut *self);
+        self.write = write;
+
+        let out = self.wrap_variant(variant_index, variant, value?);
         if self.write {
             self.serialize_value(&out)?;
         }
@@ -450,7 +1041,7 @@ impl<'a, W: Write> serde::Serializer for &'a mut Serializer<W> {
     }
 
     fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
-        Ok(SeqSerializer::new(self))
+        Ok(SeqSerializer::new(self, None))
     }
 
     fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
@@ -469,18 +1060,14 @@ impl<'a, W: Write> serde::Serializer for &'a mut Serializer<W> {
         self,
         _name: &'static str,
         variant_index: u32,
-        _variant: &'static str,
-        len: usize,
+        variant: &'static str,
+        _len: usize,
     ) -> Result<Self::SerializeTupleVariant, Self::Error> {
-        if self.write {
-            self.writer.write_all(&[TAG])?;
-            self.serialize_size(variant_index as usize)?;
-        }
-        self.serialize_seq(Some(len))
+        Ok(SeqSerializer::new(self, Some((variant_index, variant))))
     }
 
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
-        Ok(MapSerializer::new(self, None))
+        Ok(MapSerializer::new(self, None, None))
     }
 
     fn serialize_struct(
@@ -488,21 +1075,20 @@ impl<'a, W: Write> serde::Serializer for &'a mut Serializer<W> {
         _name: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStruct, Self::Error> {
-        Ok(MapSerializer::new(self, Some(ObjectKind::String)))
+        Ok(MapSerializer::new_struct(self, None))
     }
 
     fn serialize_struct_variant(
         self,
         _name: &'static str,
         variant_index: u32,
-        _variant: &'static str,
-        len: usize,
+        variant: &'static str,
+        _len: usize,
     ) -> Result<Self::SerializeStructVariant, Self::Error> {
-        if self.write {
-            self.writer.write_all(&[TAG])?;
-            self.serialize_size(variant_index as usize)?;
-        }
-        self.serialize_struct("", len)
+        Ok(MapSerializer::new_struct(
+            self,
+            Some((variant_index, variant)),
+        ))
     }
 }
 
@@ -520,3 +1106,75 @@ pub fn to_bytes(value: &impl serde::Serialize) -> Result<Vec<u8>, Error> {
     to_writer(&mut writer, value)?;
     Ok(writer)
 }
+
+/// Serializes the `value` into the `buf`, returning the number of bytes written.
+///
+/// If the `buf` is too small, [`Error::BufferFull`] is returned with the number of bytes needed,
+/// and the contents of the `buf` are unspecified.
+///
+/// This serializes like [`to_writer_streaming`], with the same restrictions, so nothing is
+/// allocated along the way. The exceptions are tuples (including fixed-size arrays) and the
+/// extension types such as [`Complex`](crate::Complex) and [`Matrix`](crate::Matrix), which are
+/// built as [`Value`]s before being written.
+pub fn to_slice(buf: &mut [u8], value: &impl serde::Serialize) -> Result<usize, Error> {
+    let mut writer = SliceWriter { buf, written: 0 };
+    to_writer_streaming(&mut writer, value)?;
+    if writer.written > writer.buf.len() {
+        Err(Error::BufferFull {
+            needed: Some(writer.written),
+        })
+    } else {
+        Ok(writer.written)
+    }
+}
+
+/// A writer into a fixed-size buffer that keeps counting bytes once the buffer is full.
+struct SliceWriter<'a> {
+    buf: &'a mut [u8],
+    written: usize,
+}
+
+impl Write for SliceWriter<'_> {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        let end = self.written + data.len();
+        if let Some(dest) = self.buf.get_mut(self.written..end) {
+            dest.copy_from_slice(data);
+        }
+        self.written = end;
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Returns the number of bytes the `value` would be serialized into, without producing any output.
+///
+/// This uses the default options, and is the size of the buffer [`to_slice`] needs. Use
+/// [`Serializer::serialized_size`] for other options.
+pub fn serialized_size(value: &impl serde::Serialize) -> Result<u64, Error> {
+    Serializer::new(std::io::sink()).serialized_size(value)
+}
+
+/// A writer that discards everything written to it, counting the bytes.
+struct ByteCounter(u64);
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0 += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Serializes the `value` into the `writer` without building up an intermediate [`Value`].
+///
+/// See [`StreamingSerializer`] for the restrictions this imposes.
+pub fn to_writer_streaming(writer: impl Write, value: &impl serde::Serialize) -> Result<(), Error> {
+    let mut serializer = StreamingSerializer::new(writer);
+    value.serialize(&mut serializer)
+}
@@ -0,0 +1,87 @@
+use crate::Value;
+
+/// Replaces every NaN with the canonical quiet NaN and every negative zero with a positive zero.
+///
+/// Scalars and typed arrays of floats are canonicalized. Anything else is returned as-is.
+pub fn canonicalize_floats(value: Value) -> Value {
+    match value {
+        Value::F32(v) => Value::F32(canonical_f32(v)),
+        Value::F64(v) => Value::F64(canonical_f64(v)),
+        Value::F32Array(v) => Value::F32Array(v.into_iter().map(canonical_f32).collect()),
+        Value::F64Array(v) => Value::F64Array(v.into_iter().map(canonical_f64).collect()),
+        #[cfg(feature = "half")]
+        Value::F16(v) => Value::F16(canonical_f16(v)),
+        #[cfg(feature = "half")]
+        Value::BF16(v) => Value::BF16(canonical_bf16(v)),
+        #[cfg(feature = "half")]
+        Value::F16Array(v) => Value::F16Array(v.into_iter().map(canonical_f16).collect()),
+        #[cfg(feature = "half")]
+        Value::BF16Array(v) => Value::BF16Array(v.into_iter().map(canonical_bf16).collect()),
+        value => value,
+    }
+}
+
+pub fn canonical_f32(v: f32) -> f32 {
+    if v.is_nan() {
+        f32::NAN
+    } else if v == 0.0 {
+        0.0
+    } else {
+        v
+    }
+}
+
+pub fn canonical_f64(v: f64) -> f64 {
+    if v.is_nan() {
+        f64::NAN
+    } else if v == 0.0 {
+        0.0
+    } else {
+        v
+    }
+}
+
+#[cfg(feature = "half")]
+pub fn canonical_f16(v: half::f16) -> half::f16 {
+    if v.is_nan() {
+        half::f16::NAN
+    } else if v == half::f16::ZERO {
+        half::f16::ZERO
+    } else {
+        v
+    }
+}
+
+#[cfg(feature = "half")]
+pub fn canonical_bf16(v: half::bf16) -> half::bf16 {
+    if v.is_nan() {
+        half::bf16::NAN
+    } else if v == half::bf16::ZERO {
+        half::bf16::ZERO
+    } else {
+        v
+    }
+}
+
+/// Sorts the fields of an object by their keys.
+///
+/// String keys are ordered by their bytes, and integer keys by their value.
+pub fn sort_keys(value: &mut Value) {
+    match value {
+        Value::StringObject(fields) => fields.sort_by(|(a, _), (b, _)| a.cmp(b)),
+
+        Value::I8Object(fields) => fields.sort_by_key(|(k, _)| *k),
+        Value::I16Object(fields) => fields.sort_by_key(|(k, _)| *k),
+        Value::I32Object(fields) => fields.sort_by_key(|(k, _)| *k),
+        Value::I64Object(fields) => fields.sort_by_key(|(k, _)| *k),
+        Value::I128Object(fields) => fields.sort_by_key(|(k, _)| *k),
+
+        Value::U8Object(fields) => fields.sort_by_key(|(k, _)| *k),
+        Value::U16Object(fields) => fields.sort_by_key(|(k, _)| *k),
+        Value::U32Object(fields) => fields.sort_by_key(|(k, _)| *k),
+        Value::U64Object(fields) => fields.sort_by_key(|(k, _)| *k),
+        Value::U128Object(fields) => fields.sort_by_key(|(k, _)| *k),
+
+        _ => {}
+    }
+}
@@ -0,0 +1,119 @@
+use crate::Value;
+use std::borrow::Cow;
+
+/// Narrows a number to the smallest type of the same kind that can hold it without loss.
+///
+/// Floats are only narrowed if converting them to an [`f32`] and back yields the same value, so
+/// NaNs are never narrowed. Typed arrays are narrowed as a whole, to the smallest type that fits
+/// every element. Anything else is returned as-is.
+pub fn narrow(value: Value) -> Value {
+    match value {
+        Value::I16(v) => narrow_signed(v as i128),
+        Value::I32(v) => narrow_signed(v as i128),
+        Value::I64(v) => narrow_signed(v as i128),
+        Value::I128(v) => narrow_signed(v),
+
+        Value::U16(v) => narrow_unsigned(v as u128),
+        Value::U32(v) => narrow_unsigned(v as u128),
+        Value::U64(v) => narrow_unsigned(v as u128),
+        Value::U128(v) => narrow_unsigned(v),
+
+        Value::F64(v) => {
+            if (v as f32) as f64 == v {
+                Value::F32(v as f32)
+            } else {
+                Value::F64(v)
+            }
+        }
+
+        Value::I16Array(v) => narrow_signed_array(v, Value::I16Array),
+        Value::I32Array(v) => narrow_signed_array(v, Value::I32Array),
+        Value::I64Array(v) => narrow_signed_array(v, Value::I64Array),
+        Value::I128Array(v) => narrow_signed_array(v, Value::I128Array),
+
+        Value::U16Array(v) => narrow_unsigned_array(v, Value::U16Array),
+        Value::U32Array(v) => narrow_unsigned_array(v, Value::U32Array),
+        Value::U64Array(v) => narrow_unsigned_array(v, Value::U64Array),
+        Value::U128Array(v) => narrow_unsigned_array(v, Value::U128Array),
+
+        Value::F64Array(v) => {
+            if !v.is_empty() && v.iter().all(|v| (*v as f32) as f64 == *v) {
+                Value::F32Array(v.into_iter().map(|v| v as f32).collect())
+            } else {
+                Value::F64Array(v)
+            }
+        }
+
+        value => value,
+    }
+}
+
+fn narrow_signed(v: i128) -> Value {
+    if let Ok(v) = i8::try_from(v) {
+        Value::I8(v)
+    } else if let Ok(v) = i16::try_from(v) {
+        Value::I16(v)
+    } else if let Ok(v) = i32::try_from(v) {
+        Value::I32(v)
+    } else if let Ok(v) = i64::try_from(v) {
+        Value::I64(v)
+    } else {
+        Value::I128(v)
+    }
+}
+
+fn narrow_unsigned(v: u128) -> Value {
+    if let Ok(v) = u8::try_from(v) {
+        Value::U8(v)
+    } else if let Ok(v) = u16::try_from(v) {
+        Value::U16(v)
+    } else if let Ok(v) = u32::try_from(v) {
+        Value::U32(v)
+    } else if let Ok(v) = u64::try_from(v) {
+        Value::U64(v)
+    } else {
+        Value::U128(v)
+    }
+}
+
+fn narrow_signed_array<T: Copy + Into<i128>>(v: Vec<T>, original: fn(Vec<T>) -> Value) -> Value {
+    let (Some(min), Some(max)) = (
+        v.iter().map(|v| (*v).into()).min(),
+        v.iter().map(|v| (*v).into()).max(),
+    ) else {
+        return original(v);
+    };
+    let width = size_of::<T>();
+    let fits = |lo: i128, hi: i128| lo <= min && max <= hi;
+
+    if width > 1 && fits(i8::MIN as i128, i8::MAX as i128) {
+        Value::I8Array(v.into_iter().map(|v| v.into() as i8).collect())
+    } else if width > 2 && fits(i16::MIN as i128, i16::MAX as i128) {
+        Value::I16Array(v.into_iter().map(|v| v.into() as i16).collect())
+    } else if width > 4 && fits(i32::MIN as i128, i32::MAX as i128) {
+        Value::I32Array(v.into_iter().map(|v| v.into() as i32).collect())
+    } else if width > 8 && fits(i64::MIN as i128, i64::MAX as i128) {
+        Value::I64Array(v.into_iter().map(|v| v.into() as i64).collect())
+    } else {
+        original(v)
+    }
+}
+
+fn narrow_unsigned_array<T: Copy + Into<u128>>(v: Vec<T>, original: fn(Vec<T>) -> Value) -> Value {
+    let Some(max) = v.iter().map(|v| (*v).into()).max() else {
+        return original(v);
+    };
+    let width = size_of::<T>();
+
+    if width > 1 && max <= u8::MAX as u128 {
+        Value::U8Array(Cow::Owned(v.into_iter().map(|v| v.into() as u8).collect()))
+    } else if width > 2 && max <= u16::MAX as u128 {
+        Value::U16Array(v.into_iter().map(|v| v.into() as u16).collect())
+    } else if width > 4 && max <= u32::MAX as u128 {
+        Value::U32Array(v.into_iter().map(|v| v.into() as u32).collect())
+    } else if width > 8 && max <= u64::MAX as u128 {
+        Value::U64Array(v.into_iter().map(|v| v.into() as u64).collect())
+    } else {
+        original(v)
+    }
+}
@@ -1,10 +1,10 @@
-use super::{SeqSerializer, Serializer};
+use super::{SeqSerializer, Serializer, canonical};
 use crate::{Value, error::Error, headers::ObjectKind};
 use serde::{
     Serialize,
     ser::{SerializeMap, SerializeStruct, SerializeStructVariant},
 };
-use std::io::Write;
+use std::{borrow::Cow, io::Write};
 
 pub struct MapSerializer<'a, W: Write> {
     serializer: &'a mut Serializer<W>,
@@ -12,17 +12,58 @@ pub struct MapSerializer<'a, W: Write> {
     keys: Vec<Value>,
     values: Vec<Value>,
     key: bool,
+    none: bool,
+    positional: bool,
+    /// The index of the next field, when struct fields are keyed by index.
+    field_index: Option<usize>,
+    write: bool,
+    variant: Option<(u32, &'static str)>,
 }
 
 impl<'a, W: Write> MapSerializer<'a, W> {
-    pub fn new(serializer: &'a mut Serializer<W>, kind: Option<ObjectKind>) -> Self {
-        serializer.write = false;
+    /// If a `variant` (index and name) is given, the object is serialized as the payload of that
+    /// enum variant.
+    pub fn new(
+        serializer: &'a mut Serializer<W>,
+        kind: Option<ObjectKind>,
+        variant: Option<(u32, &'static str)>,
+    ) -> Self {
+        let write = std::mem::replace(&mut serializer.write, false);
         Self {
             serializer,
             kind,
             keys: Vec::new(),
             values: Vec::new(),
             key: false,
+            none: false,
+            positional: false,
+            field_index: None,
+            write,
+            variant,
+        }
+    }
+
+    /// Creates a serializer for the fields of a struct, which are written as a generic array if
+    /// [`Serializer::structs_as_arrays`] is enabled, or keyed by their index if
+    /// [`Serializer::struct_field_indices`] is enabled.
+    pub fn new_struct(
+        serializer: &'a mut Serializer<W>,
+        variant: Option<(u32, &'static str)>,
+    ) -> Self {
+        let positional = serializer.structs_as_arrays;
+        let indexed = serializer.struct_field_indices && !positional;
+        Self {
+            positional,
+            field_index: indexed.then_some(0),
+            ..Self::new(
+                serializer,
+                Some(if indexed {
+                    ObjectKind::U16
+                } else {
+                    ObjectKind::String
+                }),
+                variant,
+            )
         }
     }
 
@@ -39,6 +80,89 @@ impl<'a, W: Write> MapSerializer<'a, W> {
         }
         Ok(())
     }
+
+    /// Records that `error` occurred within the value of the last key.
+    fn in_last_key(&self, error: Error) -> Error {
+        match self.keys.last() {
+            Some(Value::String(key)) => error.in_field(&String::from_utf8_lossy(key)),
+            Some(Value::I8(key)) => error.in_element(key),
+            Some(Value::I16(key)) => error.in_element(key),
+            Some(Value::I32(key)) => error.in_element(key),
+            Some(Value::I64(key)) => error.in_element(key),
+            Some(Value::I128(key)) => error.in_element(key),
+            Some(Value::U8(key)) => error.in_element(key),
+            Some(Value::U16(key)) => error.in_element(key),
+            Some(Value::U32(key)) => error.in_element(key),
+            Some(Value::U64(key)) => error.in_element(key),
+            Some(Value::U128(key)) => error.in_element(key),
+            _ => error,
+        }
+    }
+
+    fn skip_field_index(&mut self) {
+        if let Some(index) = &mut self.field_index {
+            *index += 1;
+        }
+    }
+
+    /// Skips a field of a struct, which only works if it isn't written positionally.
+    fn skip_struct_field(&mut self, key: &'static str) -> Result<(), Error> {
+        if self.positional {
+            return Err(Error::SkippedField(key));
+        }
+        self.skip_field_index();
+        Ok(())
+    }
+
+    /// Keys are kept at their declared width, since every key in an object shares one type.
+    fn serialize_int(&mut self, kind: ObjectKind, value: Value) -> Result<Value, Error> {
+        self.ensure_kind(kind)?;
+        if self.key {
+            Ok(value)
+        } else {
+            self.serializer.serialize_number(value)
+        }
+    }
+
+    /// Field names live for `'static`, so they can be borrowed rather than copied.
+    fn serialize_field_key(&mut self, key: &'static str) -> Result<(), Error> {
+        self.key = true;
+        if let Some(index) = self.field_index {
+            let index = u16::try_from(index).map_err(|_| Error::TooLong)?;
+            self.keys.push(Value::U16(index));
+            self.skip_field_index();
+            return Ok(());
+        }
+        self.ensure_kind(ObjectKind::String)?;
+        self.keys.push(Value::String(Cow::Borrowed(key.as_bytes())));
+        Ok(())
+    }
+}
+
+impl<'a, W: Write> MapSerializer<'a, W> {
+    fn serialize_struct_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        if self.positional {
+            // Every field has to be written to keep the positions of the following ones
+            self.key = false;
+            let value = value.serialize(&mut *self).map_err(|e| e.in_field(key))?;
+            self.values.push(value);
+            return Ok(());
+        }
+
+        self.serialize_field_key(key)?;
+        self.key = false;
+        self.none = false;
+        let value = value.serialize(&mut *self).map_err(|e| e.in_field(key))?;
+        if self.none && self.serializer.skip_none_fields {
+            self.keys.pop();
+        } else {
+            self.values.push(value);
+        }
+        Ok(())
+    }
 }
 
 impl<'a, W: Write> SerializeMap for MapSerializer<'a, W> {
@@ -60,13 +184,28 @@ impl<'a, W: Write> SerializeMap for MapSerializer<'a, W> {
         T: ?Sized + serde::Serialize,
     {
         self.key = false;
-        let value = value.serialize(&mut *self)?;
+        let value = value
+            .serialize(&mut *self)
+            .map_err(|e| self.in_last_key(e))?;
         self.values.push(value);
         Ok(())
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        let value = match self.kind {
+        let mut value = match self.kind {
+            _ if self.positional => Value::GenericArray(self.values),
+            // Field indices are written as `u8`s if they all fit
+            Some(ObjectKind::U16)
+                if self.field_index.is_some()
+                    && self.keys.iter().all(|k| matches!(k, Value::U16(0..=255))) =>
+            {
+                let keys = self.keys.into_iter().map(|v| match v {
+                    Value::U16(v) => v as u8,
+                    _ => unreachable!(),
+                });
+                let fields = keys.zip(self.values).collect();
+                Value::U8Object(fields)
+            }
             None => Value::StringObject(vec![]),
             Some(ObjectKind::String) => {
                 let keys = self.keys.into_iter().map(|v| match v {
@@ -159,8 +298,18 @@ impl<'a, W: Write> SerializeMap for MapSerializer<'a, W> {
             }
         };
 
-        self.serializer.write = true;
-        self.serializer.serialize_value(&value)?;
+        if self.serializer.canonical {
+            canonical::sort_keys(&mut value);
+        }
+        let value = match self.variant {
+            Some((index, name)) => self.serializer.wrap_variant(index, name, value),
+            None => value,
+        };
+
+        self.serializer.write = self.write;
+        if self.write {
+            self.serializer.serialize_value(&value)?;
+        }
         Ok(value)
     }
 }
@@ -173,9 +322,11 @@ impl<'a, W: Write> SerializeStruct for MapSerializer<'a, W> {
     where
         T: ?Sized + serde::Serialize,
     {
-        self.serialize_key(key)?;
-        self.serialize_value(value)?;
-        Ok(())
+        self.serialize_struct_field(key, value)
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), Self::Error> {
+        self.skip_struct_field(key)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
@@ -191,9 +342,11 @@ impl<'a, W: Write> SerializeStructVariant for MapSerializer<'a, W> {
     where
         T: ?Sized + Serialize,
     {
-        self.serialize_key(key)?;
-        self.serialize_value(value)?;
-        Ok(())
+        self.serialize_struct_field(key, value)
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), Self::Error> {
+        self.skip_struct_field(key)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
@@ -213,6 +366,10 @@ impl<'a, 'b, W: Write> serde::Serializer for &'b mut MapSerializer<'a, W> {
     type SerializeStruct = MapSerializer<'b, W>;
     type SerializeStructVariant = MapSerializer<'b, W>;
 
+    fn is_human_readable(&self) -> bool {
+        self.serializer.human_readable
+    }
+
     fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
         if self.key {
             Err(Error::InvalidKey)
@@ -222,53 +379,43 @@ impl<'a, 'b, W: Write> serde::Serializer for &'b mut MapSerializer<'a, W> {
     }
 
     fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
-        self.ensure_kind(ObjectKind::I8)?;
-        self.serializer.serialize_i8(v)
+        self.serialize_int(ObjectKind::I8, Value::I8(v))
     }
 
     fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
-        self.ensure_kind(ObjectKind::I16)?;
-        self.serializer.serialize_i16(v)
+        self.serialize_int(ObjectKind::I16, Value::I16(v))
     }
 
     fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
-        self.ensure_kind(ObjectKind::I32)?;
-        self.serializer.serialize_i32(v)
+        self.serialize_int(ObjectKind::I32, Value::I32(v))
     }
 
     fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
-        self.ensure_kind(ObjectKind::I64)?;
-        self.serializer.serialize_i64(v)
+        self.serialize_int(ObjectKind::I64, Value::I64(v))
     }
 
     fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
-        self.ensure_kind(ObjectKind::I128)?;
-        self.serializer.serialize_i128(v)
+        self.serialize_int(ObjectKind::I128, Value::I128(v))
     }
 
     fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
-        self.ensure_kind(ObjectKind::U8)?;
-        self.serializer.serialize_u8(v)
+        self.serialize_int(ObjectKind::U8, Value::U8(v))
     }
 
     fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
-        self.ensure_kind(ObjectKind::U16)?;
-        self.serializer.serialize_u16(v)
+        self.serialize_int(ObjectKind::U16, Value::U16(v))
     }
 
     fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
-        self.ensure_kind(ObjectKind::U32)?;
-        self.serializer.serialize_u32(v)
+        self.serialize_int(ObjectKind::U32, Value::U32(v))
     }
 
     fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
-        self.ensure_kind(ObjectKind::U64)?;
-        self.serializer.serialize_u64(v)
+        self.serialize_int(ObjectKind::U64, Value::U64(v))
     }
 
     fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
-        self.ensure_kind(ObjectKind::U128)?;
-        self.serializer.serialize_u128(v)
+        self.serialize_int(ObjectKind::U128, Value::U128(v))
     }
 
     fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
@@ -288,7 +435,7 @@ impl<'a, 'b, W: Write> serde::Serializer for &'b mut MapSerializer<'a, W> {
     }
 
     fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
-        self.serialize_str(&v.to_string())
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
     }
 
     fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
@@ -305,6 +452,7 @@ impl<'a, 'b, W: Write> serde::Serializer for &'b mut MapSerializer<'a, W> {
     }
 
     fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        self.none = true;
         self.serialize_unit()
     }
 
@@ -334,7 +482,9 @@ impl<'a, 'b, W: Write> serde::Serializer for &'b mut MapSerializer<'a, W> {
         variant: &'static str,
     ) -> Result<Self::Ok, Self::Error> {
         if self.key {
-            Err(Error::InvalidKey)
+            // Written by name, like struct fields
+            self.ensure_kind(ObjectKind::String)?;
+            Ok(Value::String(Cow::Borrowed(variant.as_bytes())))
         } else {
             self.serializer
                 .serialize_unit_variant(name, variant_index, variant)
@@ -349,7 +499,14 @@ impl<'a, 'b, W: Write> serde::Serializer for &'b mut MapSerializer<'a, W> {
     where
         T: ?Sized + serde::Serialize,
     {
-        self.serializer.serialize_newtype_struct(name, value)
+        if !self.key {
+            return self.serializer.serialize_newtype_struct(name, value);
+        }
+        // Half floats are newtypes around their bits, but floats can't be keys
+        if name == "f16" || name == "bf16" {
+            return Err(Error::InvalidKey);
+        }
+        value.serialize(self)
     }
 
     fn serialize_newtype_variant<T>(
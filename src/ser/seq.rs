@@ -10,15 +10,24 @@ pub struct SeqSerializer<'a, W: Write> {
     serializer: &'a mut Serializer<W>,
     kind: Option<ArrayKind>,
     elements: Vec<Value>,
+    /// The indices of the elements that are typed arrays, which are never narrowed.
+    typed_arrays: Vec<usize>,
+    write: bool,
+    variant: Option<(u32, &'static str)>,
 }
 
 impl<'a, W: Write> SeqSerializer<'a, W> {
-    pub fn new(serializer: &'a mut Serializer<W>) -> Self {
-        serializer.write = false;
+    /// If a `variant` (index and name) is given, the array is serialized as the payload of that
+    /// enum variant.
+    pub fn new(serializer: &'a mut Serializer<W>, variant: Option<(u32, &'static str)>) -> Self {
+        let write = std::mem::replace(&mut serializer.write, false);
         Self {
             serializer,
             kind: None,
             elements: Vec::new(),
+            typed_arrays: Vec::new(),
+            write,
+            variant,
         }
     }
 
@@ -28,7 +37,10 @@ impl<'a, W: Write> SeqSerializer<'a, W> {
             Some(ArrayKind::Generic) => {}
             Some(kind) => {
                 if kind != new {
-                    self.kind = Some(ArrayKind::Generic);
+                    self.kind = match promote(kind, new) {
+                        Some(kind) if self.serializer.promote_arrays => Some(kind),
+                        _ => Some(ArrayKind::Generic),
+                    };
                 }
             }
         }
@@ -41,6 +53,122 @@ impl<'a, W: Write> SeqSerializer<'a, W> {
     }
 }
 
+const SIGNED: [ArrayKind; 5] = [
+    ArrayKind::I8,
+    ArrayKind::I16,
+    ArrayKind::I32,
+    ArrayKind::I64,
+    ArrayKind::I128,
+];
+const UNSIGNED: [ArrayKind; 5] = [
+    ArrayKind::U8,
+    ArrayKind::U16,
+    ArrayKind::U32,
+    ArrayKind::U64,
+    ArrayKind::U128,
+];
+const FLOAT: [ArrayKind; 2] = [ArrayKind::F32, ArrayKind::F64];
+
+/// Returns the narrowest array kind that can hold the elements of both `a` and `b` without loss,
+/// if they are numbers of the same family.
+fn promote(a: ArrayKind, b: ArrayKind) -> Option<ArrayKind> {
+    [SIGNED.as_slice(), &UNSIGNED, &FLOAT]
+        .into_iter()
+        .find_map(|family| {
+            let a = family.iter().position(|k| *k == a)?;
+            let b = family.iter().position(|k| *k == b)?;
+            Some(family[a.max(b)])
+        })
+}
+
+/// Appends the parts of one complex number or array to those of another of the same type.
+fn join_parts(a: Value, b: Value) -> Value {
+    match (a, b) {
+        (Value::F32Array(mut a), Value::F32Array(b)) => {
+            a.extend(b);
+            Value::F32Array(a)
+        }
+        (Value::F64Array(mut a), Value::F64Array(b)) => {
+            a.extend(b);
+            Value::F64Array(a)
+        }
+        (Value::I8Array(mut a), Value::I8Array(b)) => {
+            a.extend(b);
+            Value::I8Array(a)
+        }
+        (Value::I16Array(mut a), Value::I16Array(b)) => {
+            a.extend(b);
+            Value::I16Array(a)
+        }
+        (Value::I32Array(mut a), Value::I32Array(b)) => {
+            a.extend(b);
+            Value::I32Array(a)
+        }
+        (Value::I64Array(mut a), Value::I64Array(b)) => {
+            a.extend(b);
+            Value::I64Array(a)
+        }
+        (Value::I128Array(mut a), Value::I128Array(b)) => {
+            a.extend(b);
+            Value::I128Array(a)
+        }
+        (Value::U8Array(mut a), Value::U8Array(b)) => {
+            a.to_mut().extend_from_slice(&b);
+            Value::U8Array(a)
+        }
+        (Value::U16Array(mut a), Value::U16Array(b)) => {
+            a.extend(b);
+            Value::U16Array(a)
+        }
+        (Value::U32Array(mut a), Value::U32Array(b)) => {
+            a.extend(b);
+            Value::U32Array(a)
+        }
+        (Value::U64Array(mut a), Value::U64Array(b)) => {
+            a.extend(b);
+            Value::U64Array(a)
+        }
+        (Value::U128Array(mut a), Value::U128Array(b)) => {
+            a.extend(b);
+            Value::U128Array(a)
+        }
+        _ => unreachable!(),
+    }
+}
+
+// The elements of a typed array may be narrower than the array itself if it was promoted, so they
+// are read back at their widest and cast down to the type of the array.
+
+fn signed(value: Value) -> i128 {
+    match value {
+        Value::I8(v) => v as i128,
+        Value::I16(v) => v as i128,
+        Value::I32(v) => v as i128,
+        Value::I64(v) => v as i128,
+        Value::I128(v) => v,
+        _ => unreachable!(),
+    }
+}
+
+fn unsigned(value: Value) -> u128 {
+    match value {
+        Value::U8(v) => v as u128,
+        Value::U16(v) => v as u128,
+        Value::U32(v) => v as u128,
+        Value::U64(v) => v as u128,
+        Value::U128(v) => v,
+        _ => unreachable!(),
+    }
+}
+
+fn float(value: Value) -> f64 {
+    match value {
+        Value::F32(v) => v as f64,
+        Value::F64(v) => v,
+        _ => unreachable!(),
+    }
+}
+
 impl<'a, W: Write> SerializeSeq for SeqSerializer<'a, W> {
     type Ok = Value;
     type Error = Error;
@@ -49,127 +177,113 @@ impl<'a, W: Write> SerializeSeq for SeqSerializer<'a, W> {
     where
         T: ?Sized + Serialize,
     {
-        let v = value.serialize(&mut *self)?;
+        let index = self.elements.len();
+        let v = value
+            .serialize(&mut *self)
+            .map_err(|e| e.in_element(index))?;
         self.elements.push(v);
         Ok(())
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
         let out = match self.kind {
-            Some(ArrayKind::BF16) | Some(ArrayKind::F16) => {
-                unreachable!()
-            }
-            None | Some(ArrayKind::Generic) => Value::GenericArray(self.elements),
-            Some(ArrayKind::I8) => Value::I8Array(
+            #[cfg(feature = "half")]
+            Some(ArrayKind::BF16) => Value::BF16Array(
                 self.elements
                     .into_iter()
                     .map(|v| match v {
-                        Value::I8(v) => v,
+                        Value::BF16(v) => v,
                         _ => unreachable!(),
                     })
                     .collect(),
             ),
-            Some(ArrayKind::I16) => Value::I16Array(
+            #[cfg(feature = "half")]
+            Some(ArrayKind::F16) => Value::F16Array(
                 self.elements
                     .into_iter()
                     .map(|v| match v {
-                        Value::I16(v) => v,
+                        Value::F16(v) => v,
                         _ => unreachable!(),
                     })
                     .collect(),
             ),
-            Some(ArrayKind::I32) => Value::I32Array(
+            // Only half floats produce these kinds
+            #[cfg(not(feature = "half"))]
+            Some(ArrayKind::BF16) | Some(ArrayKind::F16) => unreachable!(),
+            None | Some(ArrayKind::Generic) => {
+                let mut typed_arrays = self.typed_arrays.into_iter().peekable();
+                Value::GenericArray(
+                    self.elements
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, v)| {
+                            // Already canonicalized, and read back as their declared type
+                            if typed_arrays.next_if_eq(&i).is_some() {
+                                v
+                            } else {
+                                self.serializer.normalize(v)
+                            }
+                        })
+                        .collect(),
+                )
+            }
+            Some(ArrayKind::I8) => {
+                Value::I8Array(self.elements.into_iter().map(|v| signed(v) as i8).collect())
+            }
+            Some(ArrayKind::I16) => Value::I16Array(
                 self.elements
                     .into_iter()
-                    .map(|v| match v {
-                        Value::I32(v) => v,
-                        _ => unreachable!(),
-                    })
+                    .map(|v| signed(v) as i16)
                     .collect(),
             ),
-            Some(ArrayKind::I64) => Value::I64Array(
+            Some(ArrayKind::I32) => Value::I32Array(
                 self.elements
                     .into_iter()
-                    .map(|v| match v {
-                        Value::I64(v) => v,
-                        _ => unreachable!(),
-                    })
+                    .map(|v| signed(v) as i32)
                     .collect(),
             ),
-            Some(ArrayKind::I128) => Value::I128Array(
+            Some(ArrayKind::I64) => Value::I64Array(
                 self.elements
                     .into_iter()
-                    .map(|v| match v {
-                        Value::I128(v) => v,
-                        _ => unreachable!(),
-                    })
+                    .map(|v| signed(v) as i64)
                     .collect(),
             ),
+            Some(ArrayKind::I128) => {
+                Value::I128Array(self.elements.into_iter().map(signed).collect())
+            }
 
             Some(ArrayKind::U8) => Value::U8Array(
                 self.elements
                     .into_iter()
-                    .map(|v| match v {
-                        Value::U8(v) => v,
-                        _ => unreachable!(),
-                    })
+                    .map(|v| unsigned(v) as u8)
                     .collect(),
             ),
             Some(ArrayKind::U16) => Value::U16Array(
                 self.elements
                     .into_iter()
-                    .map(|v| match v {
-                        Value::U16(v) => v,
-                        _ => unreachable!(),
-                    })
+                    .map(|v| unsigned(v) as u16)
                     .collect(),
             ),
             Some(ArrayKind::U32) => Value::U32Array(
                 self.elements
                     .into_iter()
-                    .map(|v| match v {
-                        Value::U32(v) => v,
-                        _ => unreachable!(),
-                    })
+                    .map(|v| unsigned(v) as u32)
                     .collect(),
             ),
             Some(ArrayKind::U64) => Value::U64Array(
                 self.elements
                     .into_iter()
-                    .map(|v| match v {
-                        Value::U64(v) => v,
-                        _ => unreachable!(),
-                    })
-                    .collect(),
-            ),
-            Some(ArrayKind::U128) => Value::U128Array(
-                self.elements
-                    .into_iter()
-                    .map(|v| match v {
-                        Value::U128(v) => v,
-                        _ => unreachable!(),
-                    })
+                    .map(|v| unsigned(v) as u64)
                     .collect(),
             ),
+            Some(ArrayKind::U128) => {
+                Value::U128Array(self.elements.into_iter().map(unsigned).collect())
+            }
 
-            Some(ArrayKind::F32) => Value::F32Array(
-                self.elements
-                    .into_iter()
-                    .map(|v| match v {
-                        Value::F32(v) => v,
-                        _ => unreachable!(),
-                    })
-                    .collect(),
-            ),
-            Some(ArrayKind::F64) => Value::F64Array(
-                self.elements
-                    .into_iter()
-                    .map(|v| match v {
-                        Value::F64(v) => v,
-                        _ => unreachable!(),
-                    })
-                    .collect(),
-            ),
+            Some(ArrayKind::F32) => {
+                Value::F32Array(self.elements.into_iter().map(|v| float(v) as f32).collect())
+            }
+            Some(ArrayKind::F64) => Value::F64Array(self.elements.into_iter().map(float).collect()),
 
             Some(ArrayKind::String) => Value::StringArray(
                 self.elements
@@ -187,25 +301,54 @@ impl<'a, W: Write> SerializeSeq for SeqSerializer<'a, W> {
                     Value::False => false,
                     _ => unreachable!(),
                 });
-                let len = values.len();
-                let mut bytes = Vec::with_capacity(len);
-                let mut byte = 0;
-                for (i, v) in values.enumerate() {
-                    byte |= v as u8;
-                    byte <<= 1;
-
-                    if i % 8 == 7 {
-                        bytes.push(byte);
-                        byte = 0;
-                    }
+                Value::BoolArray(values.len(), crate::bits::pack(values))
+            }
+            Some(ArrayKind::F128) => Value::F128Array(
+                self.elements
+                    .into_iter()
+                    .map(|v| match v {
+                        Value::F128(v) => v,
+                        _ => unreachable!(),
+                    })
+                    .collect(),
+            ),
+            Some(ArrayKind::Complex) => {
+                let parts_header = |v: &Value| match v {
+                    Value::Complex(parts) => parts.header(),
+                    _ => unreachable!(),
+                };
+                let header = parts_header(&self.elements[0]);
+                if self.elements.iter().all(|v| parts_header(v) == header) {
+                    let parts = self
+                        .elements
+                        .into_iter()
+                        .map(|v| match v {
+                            Value::Complex(parts) => *parts,
+                            _ => unreachable!(),
+                        })
+                        .reduce(join_parts)
+                        .unwrap();
+                    Value::ComplexArray(Box::new(parts))
+                } else {
+                    Value::GenericArray(
+                        self.elements
+                            .into_iter()
+                            .map(|v| self.serializer.normalize(v))
+                            .collect(),
+                    )
                 }
-                Value::BoolArray(len, bytes)
             }
-            Some(ArrayKind::Complex) => unreachable!(),
+        };
+        let out = self.serializer.normalize(out);
+        let out = match self.variant {
+            Some((index, name)) => self.serializer.wrap_variant(index, name, out),
+            None => out,
         };
 
-        self.serializer.write = true;
-        self.serializer.serialize_value(&out)?;
+        self.serializer.write = self.write;
+        if self.write {
+            self.serializer.serialize_value(&out)?;
+        }
         Ok(out)
     }
 }
@@ -270,6 +413,10 @@ impl<'a, 'b, W: Write> serde::Serializer for &'b mut SeqSerializer<'a, W> {
     type SerializeStruct = MapSerializer<'b, W>;
     type SerializeStructVariant = MapSerializer<'b, W>;
 
+    fn is_human_readable(&self) -> bool {
+        self.serializer.human_readable
+    }
+
     fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
         self.update_type(ArrayKind::Boolean);
         self.serializer.serialize_bool(v)
@@ -277,66 +424,66 @@ impl<'a, 'b, W: Write> serde::Serializer for &'b mut SeqSerializer<'a, W> {
 
     fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
         self.update_type(ArrayKind::I8);
-        self.serializer.serialize_i8(v)
+        Ok(Value::I8(v))
     }
 
     fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
         self.update_type(ArrayKind::I16);
-        self.serializer.serialize_i16(v)
+        Ok(Value::I16(v))
     }
 
     fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
         self.update_type(ArrayKind::I32);
-        self.serializer.serialize_i32(v)
+        Ok(Value::I32(v))
     }
 
     fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
         self.update_type(ArrayKind::I64);
-        self.serializer.serialize_i64(v)
+        Ok(Value::I64(v))
     }
 
     fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
         self.update_type(ArrayKind::I128);
-        self.serializer.serialize_i128(v)
+        Ok(Value::I128(v))
     }
 
     fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
         self.update_type(ArrayKind::U8);
-        self.serializer.serialize_u8(v)
+        Ok(Value::U8(v))
     }
 
     fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
         self.update_type(ArrayKind::U16);
-        self.serializer.serialize_u16(v)
+        Ok(Value::U16(v))
     }
 
     fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
         self.update_type(ArrayKind::U32);
-        self.serializer.serialize_u32(v)
+        Ok(Value::U32(v))
     }
 
     fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
         self.update_type(ArrayKind::U64);
-        self.serializer.serialize_u64(v)
+        Ok(Value::U64(v))
     }
 
     fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
         self.update_type(ArrayKind::U128);
-        self.serializer.serialize_u128(v)
+        Ok(Value::U128(v))
     }
 
     fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
         self.update_type(ArrayKind::F32);
-        self.serializer.serialize_f32(v)
+        Ok(Value::F32(v))
     }
 
     fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
         self.update_type(ArrayKind::F64);
-        self.serializer.serialize_f64(v)
+        Ok(Value::F64(v))
     }
 
     fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
-        self.serialize_str(&v.to_string())
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
     }
 
     fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
@@ -382,12 +529,51 @@ impl<'a, 'b, W: Write> serde::Serializer for &'b mut SeqSerializer<'a, W> {
 
     fn serialize_newtype_struct<T>(
         self,
-        _name: &'static str,
+        name: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
         T: ?Sized + serde::Serialize,
     {
+        if name == crate::f128::NAME {
+            let out = self.serializer.serialize_f128(value)?;
+            match out {
+                Value::F128(_) => self.update_type(ArrayKind::F128),
+                _ => self.ensure_generic(),
+            }
+            return Ok(out);
+        }
+        if crate::typed::kind(name).is_some() {
+            self.ensure_generic();
+            self.typed_arrays.push(self.elements.len());
+            return self.serializer.serialize_newtype_struct(name, value);
+        }
+        if name == crate::matrix::NAME
+            || name == crate::bits::NAME
+            || crate::typed::hint(name).is_some()
+        {
+            self.ensure_generic();
+            return self.serializer.serialize_newtype_struct(name, value);
+        }
+        if name == crate::complex::NAME {
+            // Normalized along with the rest of the array
+            let out = self.serializer.serialize_complex(value)?;
+            match out {
+                Value::Complex(_) => self.update_type(ArrayKind::Complex),
+                _ => self.ensure_generic(),
+            }
+            return Ok(out);
+        }
+        #[cfg(feature = "half")]
+        if let Some(out) = self.serializer.serialize_half(name, value)? {
+            match out {
+                Value::F16(_) => self.update_type(ArrayKind::F16),
+                Value::BF16(_) => self.update_type(ArrayKind::BF16),
+                _ => self.ensure_generic(),
+            }
+            return Ok(out);
+        }
+
         value.serialize(self)
     }
 
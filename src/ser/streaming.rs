@@ -0,0 +1,346 @@
+mod map;
+mod seq;
+mod tuple;
+
+pub use map::StreamingMapSerializer;
+pub use seq::StreamingSeqSerializer;
+pub use tuple::StreamingTupleSerializer;
+
+use super::{SeqSerializer, Serializer};
+use crate::{Value, error::Error, headers::*, typed::Hint};
+use std::io::Write;
+
+/// A serializer that writes directly to its writer instead of building up [`Value`]s.
+///
+/// The header and size of every array and object are written as soon as they are known, so the
+/// data is never held in memory twice. This comes with two restrictions:
+///
+/// - Sequences and maps must report their length up front, otherwise serialization will fail with
+///   [`Error::UnknownLength`].
+/// - The type of an array is decided by its first element. If the first element is a bare number,
+///   boolean, or string, every following element must be of the same type, otherwise
+///   serialization will fail with [`Error::MismatchedElementType`]. This includes sequences of
+///   `#[serde(untagged)]` enums, whose variants are serialized as their bare contents.
+///
+/// Tuples (including fixed-size arrays) may mix element types, and are therefore buffered like
+/// they would be by a [`Serializer`] before being written.
+///
+/// The output reads back into the same values as that of a [`Serializer`] with the same options,
+/// and is usually identical to it. It differs where the [`Serializer`] would have to see a whole
+/// array or object before writing its header:
+///
+/// - Sequences of optional values are always written as generic arrays, even if none of them are
+///   `None`.
+/// - Sequences of numbers are never narrowed as a whole by
+///   [`compact_numbers`](Serializer::compact_numbers). Numbers on their own are still narrowed.
+/// - Keys are not sorted by [`canonical`](Serializer::canonical), so the output is only canonical
+///   if the keys of every map and struct are serialized in order. Floats are still canonicalized.
+/// - `None` fields are written as null even with [`skip_none_fields`](Serializer::skip_none_fields).
+/// - With [`struct_field_indices`](Serializer::struct_field_indices), the key type is picked from
+///   the number of fields rather than from the highest index that is written.
+pub struct StreamingSerializer<W: Write> {
+    serializer: Serializer<W>,
+    /// The kind of the [`TypedSlice`](crate::TypedSlice) whose bytes are being serialized.
+    typed_array: Option<ArrayKind>,
+    /// The header of the [`Typed`](crate::Typed) collection being serialized, should it be empty.
+    empty_hint: Option<Hint>,
+}
+
+impl<W: Write> StreamingSerializer<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            serializer: Serializer::new(writer),
+            typed_array: None,
+            empty_hint: None,
+        }
+    }
+
+    fn write_header(&mut self, header: u8) -> Result<(), Error> {
+        self.serializer.writer.write_all(&[header])?;
+        Ok(())
+    }
+
+    /// Writes what precedes the payload of an enum variant: its tag, or the header and key of an
+    /// object keyed by its name if
+    /// [`Serializer::variants_by_name`](crate::Serializer::variants_by_name) is enabled.
+    fn write_variant(&mut self, variant_index: u32, variant: &'static str) -> Result<(), Error> {
+        if self.serializer.variants_by_name {
+            self.write_header(STRING_OBJECT)?;
+            self.serializer.serialize_size(1)?;
+            self.serializer.serialize_str_value(variant.as_bytes())
+        } else {
+            self.write_header(TAG)?;
+            self.serializer.serialize_size(variant_index as usize)
+        }
+    }
+}
+
+impl<W: Write> From<Serializer<W>> for StreamingSerializer<W> {
+    fn from(serializer: Serializer<W>) -> Self {
+        Self {
+            serializer,
+            typed_array: None,
+            empty_hint: None,
+        }
+    }
+}
+
+impl<'a, W: Write> serde::Serializer for &'a mut StreamingSerializer<W> {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = StreamingSeqSerializer<'a, W>;
+    type SerializeTuple = StreamingTupleSerializer<'a, W>;
+    type SerializeTupleStruct = StreamingTupleSerializer<'a, W>;
+    type SerializeTupleVariant = StreamingTupleSerializer<'a, W>;
+    type SerializeMap = StreamingMapSerializer<'a, W>;
+    type SerializeStruct = StreamingMapSerializer<'a, W>;
+    type SerializeStructVariant = StreamingMapSerializer<'a, W>;
+
+    fn is_human_readable(&self) -> bool {
+        // Typed arrays are written as their bytes either way
+        self.serializer.human_readable && self.typed_array.is_none()
+    }
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
+        self.serializer
+            .serialize_value(&if v { Value::True } else { Value::False })
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
+        self.serializer.serialize_number(Value::I8(v)).map(|_| ())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
+        self.serializer.serialize_number(Value::I16(v)).map(|_| ())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
+        self.serializer.serialize_number(Value::I32(v)).map(|_| ())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
+        self.serializer.serialize_number(Value::I64(v)).map(|_| ())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
+        self.serializer.serialize_number(Value::I128(v)).map(|_| ())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
+        self.serializer.serialize_number(Value::U8(v)).map(|_| ())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
+        self.serializer.serialize_number(Value::U16(v)).map(|_| ())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
+        self.serializer.serialize_number(Value::U32(v)).map(|_| ())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
+        self.serializer.serialize_number(Value::U64(v)).map(|_| ())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
+        self.serializer.serialize_number(Value::U128(v)).map(|_| ())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
+        self.serializer.serialize_number(Value::F32(v)).map(|_| ())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
+        self.serializer.serialize_number(Value::F64(v)).map(|_| ())
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+        self.write_header(STRING)?;
+        self.serializer.serialize_str_value(v.as_bytes())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        if let Some(kind) = self.typed_array.take() {
+            // Only numeric kinds have typed slices
+            let size = kind.element_size().unwrap();
+            if v.len() % size == 0 {
+                self.write_header(kind.array_header())?;
+                self.serializer.serialize_size(v.len() / size)?;
+                self.serializer.writer.write_all(v)?;
+                return Ok(());
+            }
+        }
+        self.write_header(U8_ARRAY)?;
+        self.serializer.serialize_str_value(v)
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        self.write_header(NULL)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        if self.serializer.variants_by_name {
+            return self.serialize_str(variant);
+        }
+        self.write_variant(variant_index, variant)?;
+        self.write_header(NULL)
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        if name == crate::f128::NAME {
+            let out = self.serializer.serialize_f128(value)?;
+            return self.serializer.serialize_value(&out);
+        }
+        if name == crate::matrix::NAME {
+            let out = self.serializer.serialize_matrix(value)?;
+            return self.serializer.serialize_value(&out);
+        }
+        if name == crate::bits::NAME {
+            let out = self.serializer.serialize_bool_array(value)?;
+            return self.serializer.serialize_value(&out);
+        }
+        if let Some(kind) = crate::typed::kind(name) {
+            if self.serializer.canonical
+                && matches!(
+                    kind,
+                    ArrayKind::F16 | ArrayKind::BF16 | ArrayKind::F32 | ArrayKind::F64
+                )
+            {
+                let out = self.serializer.serialize_typed_array(kind, value)?;
+                let out = super::canonical::canonicalize_floats(out);
+                return self.serializer.serialize_value(&out);
+            }
+            // Its bytes are written as they are, in a single write
+            self.typed_array = Some(kind);
+            let out = value.serialize(&mut *self);
+            self.typed_array = None;
+            return out;
+        }
+        if let Some(hint) = crate::typed::hint(name) {
+            self.empty_hint = Some(hint);
+            let out = value.serialize(&mut *self);
+            self.empty_hint = None;
+            return out;
+        }
+        if name == crate::complex::NAME {
+            let out = self.serializer.serialize_complex(value)?;
+            let out = self.serializer.normalize(out);
+            return self.serializer.serialize_value(&out);
+        }
+        #[cfg(feature = "half")]
+        if let Some(out) = self.serializer.serialize_half(name, value)? {
+            return self.serializer.serialize_value(&out);
+        }
+
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        self.write_variant(variant_index, variant)?;
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        let len = len.ok_or(Error::UnknownLength)?;
+        let hint = self.empty_hint.take();
+        let mut out = StreamingSeqSerializer::new(self, len);
+        if let (0, Some(Hint::Array(kind))) = (len, hint) {
+            out.write_array_header(kind)?;
+        }
+        Ok(out)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Ok(StreamingTupleSerializer(SeqSerializer::new(
+            &mut self.serializer,
+            None,
+        )))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        self.serialize_tuple(len)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        self.write_variant(variant_index, variant)?;
+        self.serialize_tuple(len)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        let len = len.ok_or(Error::UnknownLength)?;
+        match (len, self.empty_hint.take()) {
+            (0, Some(Hint::Object(kind))) => StreamingMapSerializer::new(self, len, Some(kind)),
+            _ => StreamingMapSerializer::new(self, len, None),
+        }
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        StreamingMapSerializer::new_struct(self, len)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        self.write_variant(variant_index, variant)?;
+        self.serialize_struct("", len)
+    }
+}
@@ -0,0 +1,525 @@
+use super::StreamingSerializer;
+use crate::{
+    error::Error,
+    headers::{GENERIC_ARRAY, ObjectKind},
+};
+use serde::{
+    Serialize,
+    ser::{Impossible, SerializeMap, SerializeStruct, SerializeStructVariant},
+};
+use std::io::Write;
+
+pub struct StreamingMapSerializer<'a, W: Write> {
+    serializer: &'a mut StreamingSerializer<W>,
+    len: usize,
+    index: usize,
+    kind: Option<ObjectKind>,
+    positional: bool,
+    /// The index of the next field, when struct fields are keyed by index.
+    field_index: Option<usize>,
+    key: LastKey,
+    key_mode: KeyMode,
+}
+
+/// How keys are handled when they are serialized.
+#[derive(Clone, Copy, PartialEq)]
+enum KeyMode {
+    /// Written, with string keys copied in case their value fails.
+    WriteAndCopy,
+    /// Written without copying string keys, as the key is still at hand should its value fail.
+    Write,
+    /// Only copied, to report where the value of an entry failed.
+    Copy,
+}
+
+/// The last key of an object, kept to report where errors in its value occurred.
+enum LastKey {
+    None,
+    Field(&'static str),
+    String(String),
+    Signed(i128),
+    Unsigned(u128),
+}
+
+impl LastKey {
+    fn wrap(&self, error: Error) -> Error {
+        match self {
+            Self::None => error,
+            Self::Field(name) => error.in_field(name),
+            Self::String(name) => error.in_field(name),
+            Self::Signed(key) => error.in_element(key),
+            Self::Unsigned(key) => error.in_element(key),
+        }
+    }
+}
+
+impl<'a, W: Write> StreamingMapSerializer<'a, W> {
+    pub fn new(
+        serializer: &'a mut StreamingSerializer<W>,
+        len: usize,
+        kind: Option<ObjectKind>,
+    ) -> Result<Self, Error> {
+        let mut out = Self {
+            serializer,
+            len,
+            index: 0,
+            kind: None,
+            positional: false,
+            field_index: None,
+            key: LastKey::None,
+            key_mode: KeyMode::WriteAndCopy,
+        };
+        if let Some(kind) = kind {
+            out.write_object_header(kind)?;
+        }
+        Ok(out)
+    }
+
+    /// Creates a serializer for the fields of a struct, which are written as a generic array if
+    /// [`Serializer::structs_as_arrays`](crate::Serializer::structs_as_arrays) is enabled, or
+    /// keyed by their index if
+    /// [`Serializer::struct_field_indices`](crate::Serializer::struct_field_indices) is enabled.
+    pub fn new_struct(
+        serializer: &'a mut StreamingSerializer<W>,
+        len: usize,
+    ) -> Result<Self, Error> {
+        if !serializer.serializer.structs_as_arrays {
+            if !serializer.serializer.struct_field_indices {
+                return Self::new(serializer, len, Some(ObjectKind::String));
+            }
+
+            let kind = if len <= 256 {
+                ObjectKind::U8
+            } else {
+                ObjectKind::U16
+            };
+            let mut out = Self::new(serializer, len, Some(kind))?;
+            out.field_index = Some(0);
+            return Ok(out);
+        }
+
+        serializer.write_header(GENERIC_ARRAY)?;
+        serializer.serializer.serialize_size(len)?;
+        Ok(Self {
+            serializer,
+            len,
+            index: 0,
+            kind: None,
+            positional: true,
+            field_index: None,
+            key: LastKey::None,
+            key_mode: KeyMode::WriteAndCopy,
+        })
+    }
+
+    fn write_object_header(&mut self, kind: ObjectKind) -> Result<(), Error> {
+        self.serializer.write_header(kind.header())?;
+        self.serializer.serializer.serialize_size(self.len)?;
+        self.kind = Some(kind);
+        Ok(())
+    }
+
+    /// Decides the key type of the object from its first key and ensures that all following keys
+    /// agree with it.
+    fn ensure_kind(&mut self, expected: ObjectKind) -> Result<(), Error> {
+        match self.kind {
+            None => self.write_object_header(expected),
+            Some(found) => {
+                if found != expected {
+                    Err(Error::MismatchedKeyType { expected, found })
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    fn serialize_int_key<const N: usize>(
+        &mut self,
+        kind: ObjectKind,
+        bytes: [u8; N],
+        key: LastKey,
+    ) -> Result<(), Error> {
+        self.key = key;
+        if self.key_mode != KeyMode::Copy {
+            self.ensure_kind(kind)?;
+            self.serializer.serializer.writer.write_all(&bytes)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, W: Write> StreamingMapSerializer<'a, W> {
+    fn serialize_struct_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        if self.index == self.len {
+            return Err(Error::LengthMismatch {
+                expected: self.len,
+                found: self.index + 1,
+            });
+        }
+        if !self.positional && self.field_index.is_none() {
+            // Written directly, so the name is kept without copying it like other string keys
+            self.ensure_kind(ObjectKind::String)?;
+            self.key = LastKey::Field(key);
+            self.serializer
+                .serializer
+                .serialize_str_value(key.as_bytes())?;
+        } else if let Some(index) = self.field_index {
+            match self.kind {
+                Some(ObjectKind::U8) => self.serialize_int_key(
+                    ObjectKind::U8,
+                    u8::try_from(index)
+                        .map_err(|_| Error::TooLong)?
+                        .to_le_bytes(),
+                    LastKey::Field(key),
+                )?,
+                _ => self.serialize_int_key(
+                    ObjectKind::U16,
+                    u16::try_from(index)
+                        .map_err(|_| Error::TooLong)?
+                        .to_le_bytes(),
+                    LastKey::Field(key),
+                )?,
+            }
+            self.field_index = Some(index + 1);
+        } else {
+            self.key = LastKey::Field(key);
+        }
+        self.serialize_value(value)
+    }
+
+    fn skip_field_index(&mut self) {
+        if let Some(index) = &mut self.field_index {
+            *index += 1;
+        }
+    }
+
+    /// Skips a field of a struct, which only works if it isn't written positionally.
+    fn skip_struct_field(&mut self, key: &'static str) -> Result<(), Error> {
+        if self.positional {
+            return Err(Error::SkippedField(key));
+        }
+        self.skip_field_index();
+        Ok(())
+    }
+}
+
+impl<'a, W: Write> SerializeMap for StreamingMapSerializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        if self.index == self.len {
+            return Err(Error::LengthMismatch {
+                expected: self.len,
+                found: self.index + 1,
+            });
+        }
+        key.serialize(&mut *self)
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        value
+            .serialize(&mut *self.serializer)
+            .map_err(|e| self.key.wrap(e))?;
+        self.index += 1;
+        Ok(())
+    }
+
+    fn serialize_entry<K, V>(&mut self, key: &K, value: &V) -> Result<(), Self::Error>
+    where
+        K: ?Sized + Serialize,
+        V: ?Sized + Serialize,
+    {
+        self.key_mode = KeyMode::Write;
+        let out = self.serialize_key(key);
+        self.key_mode = KeyMode::WriteAndCopy;
+        out?;
+
+        if let Err(error) = value.serialize(&mut *self.serializer) {
+            // Only now is the key copied, to report the path of the error. It was serialized just
+            // before, so doing it again can't fail.
+            self.key_mode = KeyMode::Copy;
+            let _ = key.serialize(&mut *self);
+            self.key_mode = KeyMode::WriteAndCopy;
+            return Err(self.key.wrap(error));
+        }
+        self.index += 1;
+        Ok(())
+    }
+
+    fn end(mut self) -> Result<Self::Ok, Self::Error> {
+        if self.index != self.len {
+            return Err(Error::LengthMismatch {
+                expected: self.len,
+                found: self.index,
+            });
+        }
+
+        if self.kind.is_none() && !self.positional {
+            self.write_object_header(ObjectKind::String)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a, W: Write> SerializeStruct for StreamingMapSerializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        self.serialize_struct_field(key, value)
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), Self::Error> {
+        self.skip_struct_field(key)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        SerializeMap::end(self)
+    }
+}
+
+impl<'a, W: Write> SerializeStructVariant for StreamingMapSerializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.serialize_struct_field(key, value)
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), Self::Error> {
+        self.skip_struct_field(key)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        SerializeMap::end(self)
+    }
+}
+
+// Only used for keys. Values are serialized directly by the parent `StreamingSerializer`.
+impl<'a, 'b, W: Write> serde::Serializer for &'b mut StreamingMapSerializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = Impossible<(), Error>;
+    type SerializeTuple = Impossible<(), Error>;
+    type SerializeTupleStruct = Impossible<(), Error>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = Impossible<(), Error>;
+    type SerializeStruct = Impossible<(), Error>;
+    type SerializeStructVariant = Impossible<(), Error>;
+
+    fn is_human_readable(&self) -> bool {
+        self.serializer.serializer.human_readable
+    }
+
+    fn serialize_bool(self, _v: bool) -> Result<Self::Ok, Self::Error> {
+        Err(Error::InvalidKey)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
+        self.serialize_int_key(ObjectKind::I8, v.to_le_bytes(), LastKey::Signed(v.into()))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
+        self.serialize_int_key(ObjectKind::I16, v.to_le_bytes(), LastKey::Signed(v.into()))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
+        self.serialize_int_key(ObjectKind::I32, v.to_le_bytes(), LastKey::Signed(v.into()))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
+        self.serialize_int_key(ObjectKind::I64, v.to_le_bytes(), LastKey::Signed(v.into()))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
+        self.serialize_int_key(ObjectKind::I128, v.to_le_bytes(), LastKey::Signed(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
+        self.serialize_int_key(ObjectKind::U8, v.to_le_bytes(), LastKey::Unsigned(v.into()))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
+        self.serialize_int_key(
+            ObjectKind::U16,
+            v.to_le_bytes(),
+            LastKey::Unsigned(v.into()),
+        )
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
+        self.serialize_int_key(
+            ObjectKind::U32,
+            v.to_le_bytes(),
+            LastKey::Unsigned(v.into()),
+        )
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
+        self.serialize_int_key(
+            ObjectKind::U64,
+            v.to_le_bytes(),
+            LastKey::Unsigned(v.into()),
+        )
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
+        self.serialize_int_key(ObjectKind::U128, v.to_le_bytes(), LastKey::Unsigned(v))
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<Self::Ok, Self::Error> {
+        Err(Error::InvalidKey)
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<Self::Ok, Self::Error> {
+        Err(Error::InvalidKey)
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+        // Reuses the buffer of the previous key
+        match (&mut self.key, self.key_mode) {
+            (key, KeyMode::Write) => *key = LastKey::None,
+            (LastKey::String(key), _) => {
+                key.clear();
+                key.push_str(v);
+            }
+            (key, _) => *key = LastKey::String(v.to_owned()),
+        }
+        if self.key_mode == KeyMode::Copy {
+            return Ok(());
+        }
+        self.ensure_kind(ObjectKind::String)?;
+        self.serializer.serializer.serialize_str_value(v.as_bytes())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        Err(Error::InvalidKey)
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Err(Error::InvalidKey)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Err(Error::InvalidKey)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        Err(Error::InvalidKey)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        // Half floats are newtypes around their bits, but floats can't be keys
+        if name == "f16" || name == "bf16" {
+            return Err(Error::InvalidKey);
+        }
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        Err(Error::InvalidKey)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Err(Error::InvalidKey)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Err(Error::InvalidKey)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Err(Error::InvalidKey)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(Error::InvalidKey)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Err(Error::InvalidKey)
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Err(Error::InvalidKey)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(Error::InvalidKey)
+    }
+}
@@ -0,0 +1,422 @@
+use super::{StreamingMapSerializer, StreamingSerializer, StreamingTupleSerializer};
+use crate::{
+    Value,
+    error::Error,
+    headers::{ArrayKind, COMPLEX, header_name},
+    ser::{canonical, complex_header},
+};
+use serde::{Serialize, ser::SerializeSeq};
+use std::io::Write;
+
+pub struct StreamingSeqSerializer<'a, W: Write> {
+    serializer: &'a mut StreamingSerializer<W>,
+    len: usize,
+    index: usize,
+    kind: Option<ArrayKind>,
+    bits: u8,
+    /// The header of the parts of the first element of a complex array.
+    parts_header: u8,
+}
+
+impl<'a, W: Write> StreamingSeqSerializer<'a, W> {
+    pub fn new(serializer: &'a mut StreamingSerializer<W>, len: usize) -> Self {
+        Self {
+            serializer,
+            len,
+            index: 0,
+            kind: None,
+            bits: 0,
+            parts_header: 0,
+        }
+    }
+
+    pub(super) fn write_array_header(&mut self, kind: ArrayKind) -> Result<(), Error> {
+        self.serializer.write_header(kind.array_header())?;
+        self.serializer.serializer.serialize_size(self.len)?;
+        self.kind = Some(kind);
+        Ok(())
+    }
+
+    /// Decides the type of the array from its first element and ensures that all following
+    /// elements agree with it.
+    ///
+    /// Returns whether the element is part of a typed array, and should therefore be written
+    /// without its header.
+    fn update_type(&mut self, new: ArrayKind) -> Result<bool, Error> {
+        match self.kind {
+            None => self.write_array_header(new)?,
+            Some(ArrayKind::Generic) => {}
+            Some(kind) => {
+                if kind != new {
+                    return Err(Error::MismatchedElementType {
+                        expected: kind,
+                        found: new,
+                    });
+                }
+            }
+        }
+        Ok(self.kind != Some(ArrayKind::Generic))
+    }
+
+    fn ensure_generic(&mut self) -> Result<(), Error> {
+        self.update_type(ArrayKind::Generic).map(|_| ())
+    }
+
+    /// Writes a [`Complex`](crate::Complex), with the header of a complex array if it is the
+    /// first element.
+    fn serialize_complex<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let serializer = &mut self.serializer.serializer;
+        let out = serializer.serialize_complex(value)?;
+        let parts = match (&out, self.kind) {
+            (Value::Complex(parts), None) => {
+                serializer
+                    .writer
+                    .write_all(&[COMPLEX, complex_header(parts) | 1])?;
+                serializer.serialize_size(self.len)?;
+                self.kind = Some(ArrayKind::Complex);
+                self.parts_header = parts.header();
+                parts
+            }
+            (Value::Complex(parts), Some(ArrayKind::Complex)) => {
+                if parts.header() != self.parts_header {
+                    return Err(Error::WrongType {
+                        expected: header_name(self.parts_header),
+                        found: header_name(parts.header()),
+                    });
+                }
+                parts
+            }
+            (Value::Complex(_), Some(kind)) if kind != ArrayKind::Generic => {
+                return Err(Error::MismatchedElementType {
+                    expected: kind,
+                    found: ArrayKind::Complex,
+                });
+            }
+            _ => {
+                self.ensure_generic()?;
+                let serializer = &mut self.serializer.serializer;
+                let out = serializer.normalize(out);
+                return serializer.serialize_value(&out);
+            }
+        };
+        serializer.serialize_num_data(parts)
+    }
+
+    fn serialize_number<const N: usize>(
+        &mut self,
+        kind: ArrayKind,
+        bytes: [u8; N],
+        value: Value,
+    ) -> Result<(), Error> {
+        if self.update_type(kind)? {
+            self.serializer.serializer.writer.write_all(&bytes)?;
+            Ok(())
+        } else {
+            self.serializer
+                .serializer
+                .serialize_number(value)
+                .map(|_| ())
+        }
+    }
+}
+
+impl<'a, W: Write> SerializeSeq for StreamingSeqSerializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        if self.index == self.len {
+            return Err(Error::LengthMismatch {
+                expected: self.len,
+                found: self.index + 1,
+            });
+        }
+        let index = self.index;
+        value
+            .serialize(&mut *self)
+            .map_err(|e| e.in_element(index))?;
+        self.index += 1;
+        Ok(())
+    }
+
+    fn end(mut self) -> Result<Self::Ok, Self::Error> {
+        if self.index != self.len {
+            return Err(Error::LengthMismatch {
+                expected: self.len,
+                found: self.index,
+            });
+        }
+
+        match self.kind {
+            None => self.write_array_header(ArrayKind::Generic)?,
+            Some(ArrayKind::Boolean) if self.len % 8 != 0 => {
+                self.serializer.serializer.writer.write_all(&[self.bits])?;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a, 'b, W: Write> serde::Serializer for &'b mut StreamingSeqSerializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = StreamingSeqSerializer<'b, W>;
+    type SerializeTuple = StreamingTupleSerializer<'b, W>;
+    type SerializeTupleStruct = StreamingTupleSerializer<'b, W>;
+    type SerializeTupleVariant = StreamingTupleSerializer<'b, W>;
+    type SerializeMap = StreamingMapSerializer<'b, W>;
+    type SerializeStruct = StreamingMapSerializer<'b, W>;
+    type SerializeStructVariant = StreamingMapSerializer<'b, W>;
+
+    fn is_human_readable(&self) -> bool {
+        self.serializer.serializer.human_readable
+    }
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
+        if !self.update_type(ArrayKind::Boolean)? {
+            return self.serializer.serialize_bool(v);
+        }
+
+        // Bits are packed starting from the most significant bit of each byte
+        let bit = self.index % 8;
+        self.bits |= (v as u8) << (7 - bit);
+        if bit == 7 {
+            self.serializer.serializer.writer.write_all(&[self.bits])?;
+            self.bits = 0;
+        }
+        Ok(())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
+        self.serialize_number(ArrayKind::I8, v.to_le_bytes(), Value::I8(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
+        self.serialize_number(ArrayKind::I16, v.to_le_bytes(), Value::I16(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
+        self.serialize_number(ArrayKind::I32, v.to_le_bytes(), Value::I32(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
+        self.serialize_number(ArrayKind::I64, v.to_le_bytes(), Value::I64(v))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
+        self.serialize_number(ArrayKind::I128, v.to_le_bytes(), Value::I128(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
+        self.serialize_number(ArrayKind::U8, v.to_le_bytes(), Value::U8(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
+        self.serialize_number(ArrayKind::U16, v.to_le_bytes(), Value::U16(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
+        self.serialize_number(ArrayKind::U32, v.to_le_bytes(), Value::U32(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
+        self.serialize_number(ArrayKind::U64, v.to_le_bytes(), Value::U64(v))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
+        self.serialize_number(ArrayKind::U128, v.to_le_bytes(), Value::U128(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
+        let v = if self.serializer.serializer.canonical {
+            canonical::canonical_f32(v)
+        } else {
+            v
+        };
+        self.serialize_number(ArrayKind::F32, v.to_le_bytes(), Value::F32(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
+        let v = if self.serializer.serializer.canonical {
+            canonical::canonical_f64(v)
+        } else {
+            v
+        };
+        self.serialize_number(ArrayKind::F64, v.to_le_bytes(), Value::F64(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
+        self.serialize_str(&v.to_string())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+        if self.update_type(ArrayKind::String)? {
+            self.serializer.serializer.serialize_str_value(v.as_bytes())
+        } else {
+            self.serializer.serialize_str(v)
+        }
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        self.ensure_generic()?;
+        self.serializer.serialize_bytes(v)
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        // An element that may be null can never be part of a typed array
+        self.ensure_generic()?;
+        value.serialize(&mut *self.serializer)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        self.ensure_generic()?;
+        self.serializer.serialize_unit()
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.ensure_generic()?;
+        self.serializer
+            .serialize_unit_variant(name, variant_index, variant)
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        if name == crate::complex::NAME {
+            return self.serialize_complex(value);
+        }
+        if name == crate::f128::NAME {
+            let out = self.serializer.serializer.serialize_f128(value)?;
+            return match out {
+                Value::F128(v) => self.serialize_number(ArrayKind::F128, v, out),
+                _ => {
+                    self.ensure_generic()?;
+                    self.serializer.serializer.serialize_value(&out)
+                }
+            };
+        }
+        if name == crate::matrix::NAME
+            || name == crate::bits::NAME
+            || crate::typed::kind(name).is_some()
+            || crate::typed::hint(name).is_some()
+        {
+            self.ensure_generic()?;
+            return (&mut *self.serializer).serialize_newtype_struct(name, value);
+        }
+        #[cfg(feature = "half")]
+        if let Some(out) = self.serializer.serializer.serialize_half(name, value)? {
+            return match out {
+                Value::F16(v) => self.serialize_number(ArrayKind::F16, v.to_le_bytes(), out),
+                Value::BF16(v) => self.serialize_number(ArrayKind::BF16, v.to_le_bytes(), out),
+                _ => {
+                    self.ensure_generic()?;
+                    self.serializer.serializer.serialize_value(&out)
+                }
+            };
+        }
+
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        self.ensure_generic()?;
+        self.serializer
+            .serialize_newtype_variant(name, variant_index, variant, value)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        self.ensure_generic()?;
+        self.serializer.serialize_seq(len)
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        self.ensure_generic()?;
+        self.serializer.serialize_tuple(len)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        self.ensure_generic()?;
+        self.serializer.serialize_tuple_struct(name, len)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        self.ensure_generic()?;
+        self.serializer
+            .serialize_tuple_variant(name, variant_index, variant, len)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        self.ensure_generic()?;
+        self.serializer.serialize_map(len)
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        self.ensure_generic()?;
+        self.serializer.serialize_struct(name, len)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        self.ensure_generic()?;
+        self.serializer
+            .serialize_struct_variant(name, variant_index, variant, len)
+    }
+}
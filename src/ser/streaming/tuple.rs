@@ -0,0 +1,57 @@
+use crate::{error::Error, ser::SeqSerializer};
+use serde::{
+    Serialize,
+    ser::{SerializeSeq, SerializeTuple, SerializeTupleStruct, SerializeTupleVariant},
+};
+use std::io::Write;
+
+/// Buffers a tuple with a [`SeqSerializer`] so that its elements may differ in type.
+pub struct StreamingTupleSerializer<'a, W: Write>(pub(super) SeqSerializer<'a, W>);
+
+impl<'a, W: Write> SerializeTuple for StreamingTupleSerializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        SerializeSeq::serialize_element(&mut self.0, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        SerializeSeq::end(self.0).map(|_| ())
+    }
+}
+
+impl<'a, W: Write> SerializeTupleStruct for StreamingTupleSerializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        SerializeSeq::serialize_element(&mut self.0, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        SerializeSeq::end(self.0).map(|_| ())
+    }
+}
+
+impl<'a, W: Write> SerializeTupleVariant for StreamingTupleSerializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        SerializeSeq::serialize_element(&mut self.0, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        SerializeSeq::end(self.0).map(|_| ())
+    }
+}
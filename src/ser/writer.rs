@@ -0,0 +1,362 @@
+use super::Serializer;
+use crate::{
+    Key,
+    error::Error,
+    headers::{ArrayKind, BOOL_ARRAY, ObjectKind, STRING, TAG},
+};
+use std::io::{Read, Write};
+
+/// How many bytes of array data are gathered before being handed to the underlying writer.
+const CHUNK_SIZE: usize = 8192;
+
+/// A low-level writer for emitting BEVE by hand, without going through [`serde::Serialize`].
+///
+/// Nothing checks that what is written forms valid BEVE: every object and array must be followed by
+/// as many entries as it was begun with, and every key must match the kind of its object.
+///
+/// ```
+/// use serde_beve::{ArrayKind, ObjectKind, Writer};
+///
+/// let mut bytes = Vec::new();
+/// let mut writer = Writer::new(&mut bytes);
+/// writer.begin_object(ObjectKind::String, 2)?;
+/// writer.write_key("samples")?;
+/// writer.write_typed_array(&[0.5f32, 1.5, 2.5])?;
+/// writer.write_key("tags")?;
+/// writer.begin_array(ArrayKind::String, 1)?;
+/// writer.write_str_element("raw")?;
+/// # Ok::<(), serde_beve::Error>(())
+/// ```
+pub struct Writer<W: Write> {
+    serializer: Serializer<W>,
+}
+
+impl<W: Write> Writer<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            serializer: Serializer::new(writer),
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.serializer.writer
+    }
+
+    /// Writes a raw header byte.
+    pub fn write_header(&mut self, header: u8) -> Result<(), Error> {
+        self.serializer.writer.write_all(&[header])?;
+        Ok(())
+    }
+
+    /// Writes a compressed size, as used for the lengths of strings, arrays, and objects.
+    pub fn write_size(&mut self, size: usize) -> Result<(), Error> {
+        self.serializer.serialize_size(size)
+    }
+
+    /// Writes any serializable value, as the [`Serializer`] this writer was created from would.
+    pub fn write_value<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        value.serialize(&mut self.serializer).map(|_| ())
+    }
+
+    /// Writes a number with its header.
+    pub fn write_number<T: Number>(&mut self, value: T) -> Result<(), Error> {
+        self.write_header(T::KIND.header())?;
+        self.write_elements(&[value])
+    }
+
+    /// Writes a string with its header.
+    pub fn write_str(&mut self, value: &str) -> Result<(), Error> {
+        self.write_header(STRING)?;
+        self.serializer.serialize_str_value(value.as_bytes())
+    }
+
+    /// Begins an object of `len` entries, each of which must be written as a key followed by a
+    /// value.
+    pub fn begin_object(&mut self, kind: ObjectKind, len: usize) -> Result<(), Error> {
+        self.write_header(kind.header())?;
+        self.write_size(len)
+    }
+
+    /// Writes the key of an entry in an object of kind [`ObjectKind::String`].
+    pub fn write_key(&mut self, key: &str) -> Result<(), Error> {
+        self.serializer.serialize_str_value(key.as_bytes())
+    }
+
+    /// Writes the key of an entry in an object keyed by integers, which must be an integer of the
+    /// type of the object.
+    pub fn write_int_key<T: Number + Key>(&mut self, key: T) -> Result<(), Error> {
+        self.write_elements(&[key])
+    }
+
+    /// Begins an array of `len` elements.
+    ///
+    /// The elements of a generic array are written as whole values. Those of numeric arrays must
+    /// be written with [`Writer::write_elements`], and those of string arrays with
+    /// [`Writer::write_str_element`]. Boolean arrays are written at once with
+    /// [`Writer::write_bool_array`].
+    ///
+    /// Returns [`Error::NoArrayHeader`] for complex arrays, which must be written as a whole with
+    /// [`Writer::write_value`] instead.
+    pub fn begin_array(&mut self, kind: ArrayKind, len: usize) -> Result<(), Error> {
+        if kind == ArrayKind::Complex {
+            return Err(Error::NoArrayHeader(kind));
+        }
+        self.write_header(kind.array_header())?;
+        self.write_size(len)
+    }
+
+    /// Writes elements of a numeric array, which may be split across any number of calls.
+    pub fn write_elements<T: Number>(&mut self, values: &[T]) -> Result<(), Error> {
+        // Numbers are stored little-endian, like the platform's own
+        self.serializer
+            .writer
+            .write_all(bytemuck::cast_slice(values))?;
+        Ok(())
+    }
+
+    /// Writes an element of a string array.
+    pub fn write_str_element(&mut self, value: &str) -> Result<(), Error> {
+        self.serializer.serialize_str_value(value.as_bytes())
+    }
+
+    /// Writes a whole numeric array.
+    pub fn write_typed_array<T: Number>(&mut self, values: &[T]) -> Result<(), Error> {
+        self.begin_array(T::KIND, values.len())?;
+        self.write_elements(values)
+    }
+
+    /// Writes a whole numeric array from an iterator, without collecting it first.
+    ///
+    /// Returns [`Error::LengthMismatch`] if the iterator yields a different number of elements than
+    /// it reported, in which case the output is left incomplete, and [`Error::TooLong`] if it
+    /// reports more elements than could ever fit in memory.
+    pub fn write_typed_iter<T, I>(&mut self, values: I) -> Result<(), Error>
+    where
+        T: Number,
+        I: IntoIterator<Item = T>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let values = values.into_iter();
+        let len = values.len();
+        let size = len
+            .checked_mul(std::mem::size_of::<T>())
+            .ok_or(Error::TooLong)?;
+        self.begin_array(T::KIND, len)?;
+
+        let mut found = 0;
+        let mut bytes = Vec::with_capacity(CHUNK_SIZE.min(size));
+        for v in values {
+            found += 1;
+            if found > len {
+                return Err(Error::LengthMismatch {
+                    expected: len,
+                    found,
+                });
+            }
+            bytes.extend_from_slice(bytemuck::bytes_of(&v));
+            if bytes.len() >= CHUNK_SIZE {
+                self.serializer.writer.write_all(&bytes)?;
+                bytes.clear();
+            }
+        }
+        self.serializer.writer.write_all(&bytes)?;
+        if found != len {
+            return Err(Error::LengthMismatch {
+                expected: len,
+                found,
+            });
+        }
+        Ok(())
+    }
+
+    /// Writes a whole boolean array, packing eight values into each byte.
+    pub fn write_bool_array(&mut self, values: &[bool]) -> Result<(), Error> {
+        self.write_bool_iter(values.iter().copied())
+    }
+
+    /// Writes a whole boolean array from an iterator, without collecting it first.
+    ///
+    /// Returns [`Error::LengthMismatch`] if the iterator yields a different number of elements than
+    /// it reported, in which case the output is left incomplete.
+    pub fn write_bool_iter<I>(&mut self, values: I) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = bool>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let values = values.into_iter();
+        let len = values.len();
+        self.write_header(BOOL_ARRAY)?;
+        self.write_size(len)?;
+
+        let mut found = 0;
+        let mut byte = 0;
+        let mut bytes = Vec::with_capacity(CHUNK_SIZE.min(len.div_ceil(8)));
+        for v in values {
+            if found == len {
+                return Err(Error::LengthMismatch {
+                    expected: len,
+                    found: found + 1,
+                });
+            }
+            // Bits are packed starting from the most significant bit of each byte
+            byte |= (v as u8) << (7 - found % 8);
+            found += 1;
+            if found % 8 == 0 {
+                bytes.push(byte);
+                byte = 0;
+                if bytes.len() == CHUNK_SIZE {
+                    self.serializer.writer.write_all(&bytes)?;
+                    bytes.clear();
+                }
+            }
+        }
+        if found % 8 != 0 {
+            bytes.push(byte);
+        }
+        self.serializer.writer.write_all(&bytes)?;
+        if found != len {
+            return Err(Error::LengthMismatch {
+                expected: len,
+                found,
+            });
+        }
+        Ok(())
+    }
+
+    /// Writes a whole typed array of `len` elements, copying their data as-is from `reader`.
+    ///
+    /// The data must already be in the layout BEVE expects: little-endian numbers, or booleans
+    /// packed eight to a byte starting from the most significant bit. Returns
+    /// [`Error::NoRawData`] for string, generic, and complex arrays, and
+    /// [`Error::LengthMismatch`] if `reader` ends before all elements were read.
+    pub fn write_raw_array<R: Read>(
+        &mut self,
+        kind: ArrayKind,
+        len: usize,
+        reader: R,
+    ) -> Result<(), Error> {
+        let size = match (kind, kind.element_size()) {
+            (ArrayKind::Boolean, _) => len.div_ceil(8),
+            (_, Some(element_size)) => len.checked_mul(element_size).ok_or(Error::TooLong)?,
+            (_, None) => return Err(Error::NoRawData(kind)),
+        };
+        self.begin_array(kind, len)?;
+
+        let copied =
+            std::io::copy(&mut reader.take(size as u64), &mut self.serializer.writer)? as usize;
+        if copied != size {
+            return Err(Error::LengthMismatch {
+                expected: len,
+                found: match kind.element_size() {
+                    Some(element_size) => copied / element_size,
+                    None => copied * 8,
+                },
+            });
+        }
+        Ok(())
+    }
+
+    /// Writes the tag of an enum variant, which must be followed by its payload as a value.
+    pub fn write_tag(&mut self, index: usize) -> Result<(), Error> {
+        self.write_header(TAG)?;
+        self.write_size(index)
+    }
+}
+
+impl<W: Write> From<Serializer<W>> for Writer<W> {
+    fn from(serializer: Serializer<W>) -> Self {
+        Self { serializer }
+    }
+}
+
+mod sealed {
+    /// Every number is plain old data, so arrays of them can be copied to and from bytes as-is.
+    pub trait Sealed:
+        bytemuck::Pod + Default + serde::Serialize + serde::de::DeserializeOwned
+    {
+    }
+}
+
+/// A number that can be written by a [`Writer`], either on its own or as an element of a numeric
+/// array.
+pub trait Number: Copy + sealed::Sealed {
+    /// The kind of numeric arrays of this type.
+    const KIND: ArrayKind;
+}
+
+impl sealed::Sealed for i8 {}
+impl Number for i8 {
+    const KIND: ArrayKind = ArrayKind::I8;
+}
+
+impl sealed::Sealed for i16 {}
+impl Number for i16 {
+    const KIND: ArrayKind = ArrayKind::I16;
+}
+
+impl sealed::Sealed for i32 {}
+impl Number for i32 {
+    const KIND: ArrayKind = ArrayKind::I32;
+}
+
+impl sealed::Sealed for i64 {}
+impl Number for i64 {
+    const KIND: ArrayKind = ArrayKind::I64;
+}
+
+impl sealed::Sealed for i128 {}
+impl Number for i128 {
+    const KIND: ArrayKind = ArrayKind::I128;
+}
+
+impl sealed::Sealed for u8 {}
+impl Number for u8 {
+    const KIND: ArrayKind = ArrayKind::U8;
+}
+
+impl sealed::Sealed for u16 {}
+impl Number for u16 {
+    const KIND: ArrayKind = ArrayKind::U16;
+}
+
+impl sealed::Sealed for u32 {}
+impl Number for u32 {
+    const KIND: ArrayKind = ArrayKind::U32;
+}
+
+impl sealed::Sealed for u64 {}
+impl Number for u64 {
+    const KIND: ArrayKind = ArrayKind::U64;
+}
+
+impl sealed::Sealed for u128 {}
+impl Number for u128 {
+    const KIND: ArrayKind = ArrayKind::U128;
+}
+
+impl sealed::Sealed for f32 {}
+impl Number for f32 {
+    const KIND: ArrayKind = ArrayKind::F32;
+}
+
+impl sealed::Sealed for f64 {}
+impl Number for f64 {
+    const KIND: ArrayKind = ArrayKind::F64;
+}
+
+#[cfg(feature = "half")]
+impl sealed::Sealed for half::bf16 {}
+#[cfg(feature = "half")]
+impl Number for half::bf16 {
+    const KIND: ArrayKind = ArrayKind::BF16;
+}
+
+#[cfg(feature = "half")]
+impl sealed::Sealed for half::f16 {}
+#[cfg(feature = "half")]
+impl Number for half::f16 {
+    const KIND: ArrayKind = ArrayKind::F16;
+}
@@ -0,0 +1,623 @@
+use crate::{
+    F128Bits,
+    headers::{ArrayKind, ObjectKind},
+    ser::Number,
+};
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{SeqAccess, Visitor},
+};
+use std::{
+    any::Any,
+    cell::Cell,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    fmt,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
+
+/// The names of the newtype structs typed arrays serialize as, by which the
+/// [`Serializer`](crate::Serializer) recognizes them and the kind of their elements.
+const NAMES: [(ArrayKind, &str); 14] = [
+    (ArrayKind::I8, "$serde_beve::TypedArray<i8>"),
+    (ArrayKind::I16, "$serde_beve::TypedArray<i16>"),
+    (ArrayKind::I32, "$serde_beve::TypedArray<i32>"),
+    (ArrayKind::I64, "$serde_beve::TypedArray<i64>"),
+    (ArrayKind::I128, "$serde_beve::TypedArray<i128>"),
+    (ArrayKind::U8, "$serde_beve::TypedArray<u8>"),
+    (ArrayKind::U16, "$serde_beve::TypedArray<u16>"),
+    (ArrayKind::U32, "$serde_beve::TypedArray<u32>"),
+    (ArrayKind::U64, "$serde_beve::TypedArray<u64>"),
+    (ArrayKind::U128, "$serde_beve::TypedArray<u128>"),
+    (ArrayKind::BF16, "$serde_beve::TypedArray<bf16>"),
+    (ArrayKind::F16, "$serde_beve::TypedArray<f16>"),
+    (ArrayKind::F32, "$serde_beve::TypedArray<f32>"),
+    (ArrayKind::F64, "$serde_beve::TypedArray<f64>"),
+];
+
+thread_local! {
+    /// The elements of the typed array the [`Deserializer`](crate::Deserializer) has just read.
+    ///
+    /// Serde can only give a visitor the bytes of an array, so the elements are read straight into
+    /// a `Vec` of their type, left here, and taken by the visitor of the [`TypedArray`] instead.
+    /// They are only here while that visitor runs.
+    static ELEMENTS: Cell<Option<Box<dyn Any>>> = const { Cell::new(None) };
+}
+
+/// Clears [`ELEMENTS`] when dropped, so that elements a visitor didn't take are never picked up by
+/// a later one, even if it returned early or panicked.
+struct ClearElements;
+
+impl Drop for ClearElements {
+    fn drop(&mut self) {
+        ELEMENTS.take();
+    }
+}
+
+/// Visits the elements of a typed array read by the [`Deserializer`](crate::Deserializer).
+pub(crate) fn visit_elements<'de, T: Number, V: Visitor<'de>, E: serde::de::Error>(
+    elements: Vec<T>,
+    visitor: V,
+) -> Result<V::Value, E> {
+    ELEMENTS.set(Some(Box::new(elements)));
+    let _clear = ClearElements;
+    visitor.visit_unit()
+}
+
+fn name(kind: ArrayKind) -> &'static str {
+    NAMES.iter().find(|(k, _)| *k == kind).unwrap().1
+}
+
+/// Returns the kind of the elements of the typed array that serializes as a newtype struct named
+/// `name`, if it is one.
+pub(crate) fn kind(name: &str) -> Option<ArrayKind> {
+    if !name.starts_with("$serde_beve::TypedArray<") {
+        return None;
+    }
+    NAMES.iter().find(|(_, n)| *n == name).map(|(k, _)| *k)
+}
+
+/// A borrowed numeric array, serialized by copying its elements as a whole instead of one at a
+/// time.
+///
+/// A [`StreamingSerializer`](crate::StreamingSerializer) writes the header, the size, and the
+/// elements themselves, exactly as they are stored in memory. Like the rest of the crate, this
+/// relies on the platform being little-endian.
+///
+/// Unlike other numeric arrays, typed arrays are never narrowed by
+/// [`Serializer::compact_numbers`](crate::Serializer::compact_numbers), so that they can always be
+/// read back in the same way.
+///
+/// Other formats see a sequence of the elements if they are human-readable, or the bytes of the
+/// elements otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TypedSlice<'a, T>(pub &'a [T]);
+
+/// An owned numeric array, serialized like a [`TypedSlice`].
+///
+/// It deserializes by reading all of the elements at once, from an array of its element type or of
+/// a narrower one of the same kind, as written by
+/// [`Serializer::compact_numbers`](crate::Serializer::compact_numbers). Signed and unsigned
+/// integers aren't read as each other, nor integers as floats.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct TypedArray<T>(pub Vec<T>);
+
+impl<T: Number> TypedArray<T> {
+    pub fn as_slice(&self) -> TypedSlice<'_, T> {
+        TypedSlice(&self.0)
+    }
+}
+
+impl<T> Deref for TypedSlice<'_, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        self.0
+    }
+}
+
+impl<T> Deref for TypedArray<T> {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'a, T> From<&'a [T]> for TypedSlice<'a, T> {
+    fn from(value: &'a [T]) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> From<Vec<T>> for TypedArray<T> {
+    fn from(value: Vec<T>) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> From<TypedArray<T>> for Vec<T> {
+    fn from(value: TypedArray<T>) -> Self {
+        value.0
+    }
+}
+
+impl<T: Number> Serialize for TypedSlice<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(name(T::KIND), &Elements(self.0))
+    }
+}
+
+impl<T: Number> Serialize for TypedArray<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.as_slice().serialize(serializer)
+    }
+}
+
+impl<'de, T: Number> Deserialize<'de> for TypedArray<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_newtype_struct(name(T::KIND), TypedVisitor(PhantomData))
+    }
+}
+
+struct Elements<'a, T>(&'a [T]);
+
+impl<T: Number> Serialize for Elements<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // This crate's serializers never report being human-readable while writing typed arrays
+        if serializer.is_human_readable() {
+            serializer.collect_seq(self.0)
+        } else {
+            serializer.serialize_bytes(bytemuck::cast_slice(self.0))
+        }
+    }
+}
+
+struct TypedVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Number> Visitor<'de> for TypedVisitor<T> {
+    type Value = TypedArray<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "an array of {}", T::KIND)
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        if deserializer.is_human_readable() {
+            Vec::deserialize(deserializer).map(TypedArray)
+        } else {
+            deserializer.deserialize_bytes(self)
+        }
+    }
+
+    fn visit_unit<E: serde::de::Error>(self) -> Result<Self::Value, E> {
+        match ELEMENTS
+            .take()
+            .map(|elements| elements.downcast::<Vec<T>>())
+        {
+            Some(Ok(elements)) => Ok(TypedArray(*elements)),
+            _ => Err(E::invalid_type(serde::de::Unexpected::Unit, &self)),
+        }
+    }
+
+    fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        let size = std::mem::size_of::<T>();
+        if v.len() % size != 0 {
+            return Err(E::invalid_length(v.len(), &self));
+        }
+        let mut values = vec![T::default(); v.len() / size];
+        bytemuck::cast_slice_mut(&mut values).copy_from_slice(v);
+        Ok(TypedArray(values))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(byte) = seq.next_element::<u8>()? {
+            bytes.push(byte);
+        }
+        self.visit_bytes(&bytes)
+    }
+}
+
+/// The type of the elements of an array, or of the keys of an object, that a [`Typed`] collection
+/// is written with even when it is empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Hint {
+    Array(ArrayKind),
+    Object(ObjectKind),
+}
+
+/// The names of the newtype structs [`Typed`] collections serialize as, by which the
+/// [`Serializer`](crate::Serializer) recognizes them and the type of their contents.
+const HINTS: [(Hint, &str); 28] = [
+    (
+        Hint::Array(ArrayKind::I8),
+        "$serde_beve::Typed<array of i8>",
+    ),
+    (
+        Hint::Array(ArrayKind::I16),
+        "$serde_beve::Typed<array of i16>",
+    ),
+    (
+        Hint::Array(ArrayKind::I32),
+        "$serde_beve::Typed<array of i32>",
+    ),
+    (
+        Hint::Array(ArrayKind::I64),
+        "$serde_beve::Typed<array of i64>",
+    ),
+    (
+        Hint::Array(ArrayKind::I128),
+        "$serde_beve::Typed<array of i128>",
+    ),
+    (
+        Hint::Array(ArrayKind::U8),
+        "$serde_beve::Typed<array of u8>",
+    ),
+    (
+        Hint::Array(ArrayKind::U16),
+        "$serde_beve::Typed<array of u16>",
+    ),
+    (
+        Hint::Array(ArrayKind::U32),
+        "$serde_beve::Typed<array of u32>",
+    ),
+    (
+        Hint::Array(ArrayKind::U64),
+        "$serde_beve::Typed<array of u64>",
+    ),
+    (
+        Hint::Array(ArrayKind::U128),
+        "$serde_beve::Typed<array of u128>",
+    ),
+    (
+        Hint::Array(ArrayKind::BF16),
+        "$serde_beve::Typed<array of bf16>",
+    ),
+    (
+        Hint::Array(ArrayKind::F16),
+        "$serde_beve::Typed<array of f16>",
+    ),
+    (
+        Hint::Array(ArrayKind::F32),
+        "$serde_beve::Typed<array of f32>",
+    ),
+    (
+        Hint::Array(ArrayKind::F64),
+        "$serde_beve::Typed<array of f64>",
+    ),
+    (
+        Hint::Array(ArrayKind::F128),
+        "$serde_beve::Typed<array of f128>",
+    ),
+    (
+        Hint::Array(ArrayKind::Boolean),
+        "$serde_beve::Typed<array of bool>",
+    ),
+    (
+        Hint::Array(ArrayKind::String),
+        "$serde_beve::Typed<array of str>",
+    ),
+    (
+        Hint::Object(ObjectKind::I8),
+        "$serde_beve::Typed<object of i8>",
+    ),
+    (
+        Hint::Object(ObjectKind::I16),
+        "$serde_beve::Typed<object of i16>",
+    ),
+    (
+        Hint::Object(ObjectKind::I32),
+        "$serde_beve::Typed<object of i32>",
+    ),
+    (
+        Hint::Object(ObjectKind::I64),
+        "$serde_beve::Typed<object of i64>",
+    ),
+    (
+        Hint::Object(ObjectKind::I128),
+        "$serde_beve::Typed<object of i128>",
+    ),
+    (
+        Hint::Object(ObjectKind::U8),
+        "$serde_beve::Typed<object of u8>",
+    ),
+    (
+        Hint::Object(ObjectKind::U16),
+        "$serde_beve::Typed<object of u16>",
+    ),
+    (
+        Hint::Object(ObjectKind::U32),
+        "$serde_beve::Typed<object of u32>",
+    ),
+    (
+        Hint::Object(ObjectKind::U64),
+        "$serde_beve::Typed<object of u64>",
+    ),
+    (
+        Hint::Object(ObjectKind::U128),
+        "$serde_beve::Typed<object of u128>",
+    ),
+    (
+        Hint::Object(ObjectKind::String),
+        "$serde_beve::Typed<object of str>",
+    ),
+];
+
+fn hint_name(hint: Hint) -> &'static str {
+    HINTS.iter().find(|(h, _)| *h == hint).unwrap().1
+}
+
+/// Returns the type of the contents of the [`Typed`] collection that serializes as a newtype
+/// struct named `name`, if it is one.
+pub(crate) fn hint(name: &str) -> Option<Hint> {
+    if !name.starts_with("$serde_beve::Typed<") {
+        return None;
+    }
+    HINTS.iter().find(|(_, n)| *n == name).map(|(h, _)| *h)
+}
+
+/// A collection whose headers don't depend on its contents, so that it is written with the header
+/// of its element or key type even when it is empty.
+///
+/// Serde doesn't tell serializers the element types of sequences or the key types of maps, so
+/// their headers are decided by their contents. With nothing to go by, empty sequences are written
+/// as generic arrays and empty maps as string-keyed objects. Wrapping them in this type writes
+/// them as, for example, an empty array of `f64`s or an empty object keyed by `u32`s instead,
+/// which other implementations may require.
+///
+/// Non-empty collections are written the same either way, and other formats see the collection
+/// itself.
+///
+/// ```
+/// use serde_beve::{Typed, to_bytes};
+/// use std::collections::HashMap;
+///
+/// // An empty array of `f64`s, rather than an empty generic array
+/// assert_eq!(to_bytes(&Typed(Vec::<f64>::new()))?, [0x64, 0x00]);
+/// // An empty object keyed by `u32`s, rather than by strings
+/// let empty: HashMap<u32, String> = HashMap::new();
+/// assert_eq!(to_bytes(&Typed(empty))?, [0x53, 0x00]);
+/// # Ok::<(), serde_beve::Error>(())
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Typed<C>(pub C);
+
+impl<C> Deref for Typed<C> {
+    type Target = C;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<C> DerefMut for Typed<C> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<C: Collection> Serialize for Typed<C> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(C::name(), &self.0)
+    }
+}
+
+impl<'de, C: Deserialize<'de>> Deserialize<'de> for Typed<C> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        C::deserialize(deserializer).map(Typed)
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// A type whose sequences are written as typed arrays: numbers, booleans, strings, and
+/// [`F128Bits`].
+pub trait Element: sealed::Sealed {
+    #[doc(hidden)]
+    const KIND: ArrayKind;
+}
+
+/// A type whose maps are written as typed objects: strings and integers.
+pub trait Key: sealed::Sealed {
+    #[doc(hidden)]
+    const KIND: ObjectKind;
+}
+
+/// A collection that can be wrapped in [`Typed`].
+pub trait Collection: Serialize {
+    #[doc(hidden)]
+    fn name() -> &'static str;
+}
+
+impl sealed::Sealed for i8 {}
+impl Element for i8 {
+    const KIND: ArrayKind = ArrayKind::I8;
+}
+
+impl sealed::Sealed for i16 {}
+impl Element for i16 {
+    const KIND: ArrayKind = ArrayKind::I16;
+}
+
+impl sealed::Sealed for i32 {}
+impl Element for i32 {
+    const KIND: ArrayKind = ArrayKind::I32;
+}
+
+impl sealed::Sealed for i64 {}
+impl Element for i64 {
+    const KIND: ArrayKind = ArrayKind::I64;
+}
+
+impl sealed::Sealed for i128 {}
+impl Element for i128 {
+    const KIND: ArrayKind = ArrayKind::I128;
+}
+
+impl sealed::Sealed for u8 {}
+impl Element for u8 {
+    const KIND: ArrayKind = ArrayKind::U8;
+}
+
+impl sealed::Sealed for u16 {}
+impl Element for u16 {
+    const KIND: ArrayKind = ArrayKind::U16;
+}
+
+impl sealed::Sealed for u32 {}
+impl Element for u32 {
+    const KIND: ArrayKind = ArrayKind::U32;
+}
+
+impl sealed::Sealed for u64 {}
+impl Element for u64 {
+    const KIND: ArrayKind = ArrayKind::U64;
+}
+
+impl sealed::Sealed for u128 {}
+impl Element for u128 {
+    const KIND: ArrayKind = ArrayKind::U128;
+}
+
+impl sealed::Sealed for f32 {}
+impl Element for f32 {
+    const KIND: ArrayKind = ArrayKind::F32;
+}
+
+impl sealed::Sealed for f64 {}
+impl Element for f64 {
+    const KIND: ArrayKind = ArrayKind::F64;
+}
+
+#[cfg(feature = "half")]
+impl sealed::Sealed for half::bf16 {}
+#[cfg(feature = "half")]
+impl Element for half::bf16 {
+    const KIND: ArrayKind = ArrayKind::BF16;
+}
+
+#[cfg(feature = "half")]
+impl sealed::Sealed for half::f16 {}
+#[cfg(feature = "half")]
+impl Element for half::f16 {
+    const KIND: ArrayKind = ArrayKind::F16;
+}
+
+impl sealed::Sealed for bool {}
+impl Element for bool {
+    const KIND: ArrayKind = ArrayKind::Boolean;
+}
+
+impl sealed::Sealed for String {}
+impl Element for String {
+    const KIND: ArrayKind = ArrayKind::String;
+}
+impl Key for String {
+    const KIND: ObjectKind = ObjectKind::String;
+}
+
+impl sealed::Sealed for str {}
+impl Element for str {
+    const KIND: ArrayKind = ArrayKind::String;
+}
+impl Key for str {
+    const KIND: ObjectKind = ObjectKind::String;
+}
+
+impl sealed::Sealed for F128Bits {}
+impl Element for F128Bits {
+    const KIND: ArrayKind = ArrayKind::F128;
+}
+
+impl<T: ?Sized + sealed::Sealed> sealed::Sealed for &T {}
+impl<T: ?Sized + Element> Element for &T {
+    const KIND: ArrayKind = T::KIND;
+}
+impl<T: ?Sized + Key> Key for &T {
+    const KIND: ObjectKind = T::KIND;
+}
+
+impl Key for i8 {
+    const KIND: ObjectKind = ObjectKind::I8;
+}
+impl Key for i16 {
+    const KIND: ObjectKind = ObjectKind::I16;
+}
+impl Key for i32 {
+    const KIND: ObjectKind = ObjectKind::I32;
+}
+impl Key for i64 {
+    const KIND: ObjectKind = ObjectKind::I64;
+}
+impl Key for i128 {
+    const KIND: ObjectKind = ObjectKind::I128;
+}
+impl Key for u8 {
+    const KIND: ObjectKind = ObjectKind::U8;
+}
+impl Key for u16 {
+    const KIND: ObjectKind = ObjectKind::U16;
+}
+impl Key for u32 {
+    const KIND: ObjectKind = ObjectKind::U32;
+}
+impl Key for u64 {
+    const KIND: ObjectKind = ObjectKind::U64;
+}
+impl Key for u128 {
+    const KIND: ObjectKind = ObjectKind::U128;
+}
+
+impl<T: Element + Serialize> Collection for [T] {
+    fn name() -> &'static str {
+        hint_name(Hint::Array(T::KIND))
+    }
+}
+
+impl<T: Element + Serialize> Collection for Vec<T> {
+    fn name() -> &'static str {
+        hint_name(Hint::Array(T::KIND))
+    }
+}
+
+impl<T: Element + Serialize> Collection for VecDeque<T> {
+    fn name() -> &'static str {
+        hint_name(Hint::Array(T::KIND))
+    }
+}
+
+impl<T: Element + Serialize> Collection for BTreeSet<T> {
+    fn name() -> &'static str {
+        hint_name(Hint::Array(T::KIND))
+    }
+}
+
+impl<T: Element + Serialize, H> Collection for HashSet<T, H> {
+    fn name() -> &'static str {
+        hint_name(Hint::Array(T::KIND))
+    }
+}
+
+impl<K: Key + Serialize, V: Serialize> Collection for BTreeMap<K, V> {
+    fn name() -> &'static str {
+        hint_name(Hint::Object(K::KIND))
+    }
+}
+
+impl<K: Key + Serialize, V: Serialize, H> Collection for HashMap<K, V, H> {
+    fn name() -> &'static str {
+        hint_name(Hint::Object(K::KIND))
+    }
+}
+
+impl<C: ?Sized + Collection> Collection for &C {
+    fn name() -> &'static str {
+        C::name()
+    }
+}
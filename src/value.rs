@@ -1,4 +1,5 @@
-use crate::headers::*;
+use crate::{headers::*, matrix::MatrixLayout};
+use std::borrow::Cow;
 
 #[derive(Debug, PartialEq)]
 /// An intermediate representation of values used during serialization.
@@ -6,7 +7,9 @@ use crate::headers::*;
 /// This is a nearly 1:1 mapping to the `HEADER | VALUE` format in which BEVE values are encoded.
 ///
 /// Objects are represented as a vector of key-value pairs, and strings are represented as their
-/// bytes.
+/// bytes. Strings, string keys, and byte arrays may borrow `'static` bytes, which the
+/// [`Serializer`](crate::Serializer) uses to avoid copying struct field and variant names.
+#[non_exhaustive]
 pub enum Value {
     Null,
     True,
@@ -27,7 +30,8 @@ pub enum Value {
     F16,
     F32(f32),
     F64(f64),
-    F128,
+    /// The little-endian bytes of a 128-bit float.
+    F128([u8; 16]),
 
     I8(i8),
     I16(i16),
@@ -41,9 +45,9 @@ pub enum Value {
     U64(u64),
     U128(u128),
 
-    String(Vec<u8>),
+    String(Cow<'static, [u8]>),
 
-    StringObject(Vec<(Vec<u8>, Value)>),
+    StringObject(Vec<(Cow<'static, [u8]>, Value)>),
 
     I8Object(Vec<(i8, Value)>),
     I16Object(Vec<(i16, Value)>),
@@ -67,7 +71,7 @@ pub enum Value {
     F16Array,
     F32Array(Vec<f32>),
     F64Array(Vec<f64>),
-    F128Array,
+    F128Array(Vec<[u8; 16]>),
 
     I8Array(Vec<i8>),
     I16Array(Vec<i16>),
@@ -75,7 +79,7 @@ pub enum Value {
     I64Array(Vec<i64>),
     I128Array(Vec<i128>),
 
-    U8Array(Vec<u8>),
+    U8Array(Cow<'static, [u8]>),
     U16Array(Vec<u16>),
     U32Array(Vec<u32>),
     U64Array(Vec<u64>),
@@ -83,17 +87,23 @@ pub enum Value {
 
     /// Note that field 1 constains [`u8`]s rather than [`bool`]s. This is because it is meant to
     /// be written directly to a writer during serialization, where each bit within each byte
-    /// represents a true or false value.
+    /// represents a true or false value, starting from the most significant bit.
     BoolArray(usize, Vec<u8>),
-    StringArray(Vec<Vec<u8>>),
+    StringArray(Vec<Cow<'static, [u8]>>),
     GenericArray(Vec<Value>),
 
     Delimiter,
     // Box for indirection
     // I hate it here
     Tag(usize, Box<Value>),
-    Matrix,
-    Complex,
+    /// A matrix, holding its layout, an unsigned array of its extents, and a numeric or complex
+    /// array of its values.
+    Matrix(MatrixLayout, Box<Value>, Box<Value>),
+    /// A complex number, holding a numeric array of its real and imaginary parts.
+    Complex(Box<Value>),
+    /// An array of complex numbers, holding a numeric array of their real and imaginary parts,
+    /// interleaved.
+    ComplexArray(Box<Value>),
     Reserved,
 }
 
@@ -113,7 +123,7 @@ impl Value {
             Self::F16 => F16,
             Self::F32(..) => F32,
             Self::F64(..) => F64,
-            Self::F128 => F128,
+            Self::F128(..) => F128,
 
             Self::I8(..) => I8,
             Self::I16(..) => I16,
@@ -153,7 +163,7 @@ impl Value {
             Self::F16Array => F16_ARRAY,
             Self::F32Array(..) => F32_ARRAY,
             Self::F64Array(..) => F64_ARRAY,
-            Self::F128Array => F128_ARRAY,
+            Self::F128Array(..) => F128_ARRAY,
 
             Self::I8Array(..) => I8_ARRAY,
             Self::I16Array(..) => I16_ARRAY,
@@ -173,8 +183,8 @@ impl Value {
 
             Self::Delimiter => DELIMITER,
             Self::Tag(..) => TAG,
-            Self::Matrix => MATRIX,
-            Self::Complex => COMPLEX,
+            Self::Matrix(..) => MATRIX,
+            Self::Complex(..) | Self::ComplexArray(..) => COMPLEX,
             Self::Reserved => RESERVED,
         }
     }
@@ -0,0 +1,75 @@
+mod common;
+
+use common::round_trip;
+use serde::{Deserialize, Serialize};
+use serde_beve::{Deserializer, Serializer, from_bytes, to_bytes};
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
+    time::{Duration, SystemTime},
+};
+
+#[test]
+fn ipv4_is_four_bytes() {
+    let addr = Ipv4Addr::new(192, 168, 1, 20);
+    let bytes = round_trip(&addr);
+    // Header and size of a `u8` array, followed by the octets
+    assert_eq!(bytes.len(), 2 + 4);
+    assert!(bytes.ends_with(&addr.octets()));
+}
+
+#[test]
+fn ipv6_is_sixteen_bytes() {
+    let addr = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0xff00, 0x42, 0x8329);
+    let bytes = round_trip(&addr);
+    // Header and size of a `u8` array, followed by the segments
+    assert_eq!(bytes.len(), 2 + 16);
+    assert!(bytes.ends_with(&addr.octets()));
+}
+
+#[test]
+fn ip_addr() {
+    round_trip(&IpAddr::V4(Ipv4Addr::LOCALHOST));
+    round_trip(&IpAddr::V6(Ipv6Addr::LOCALHOST));
+}
+
+#[test]
+fn socket_addr() {
+    let v4 = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(10, 0, 0, 1), 8080));
+    let v6 = SocketAddr::V6(SocketAddrV6::new(
+        Ipv6Addr::new(0x2001, 0xdb8, 0x85a3, 0x8d3, 0x1319, 0x8a2e, 0x370, 0x7348),
+        443,
+        0,
+        0,
+    ));
+    round_trip(&v4);
+    round_trip(&v6);
+    round_trip(&vec![v4, v6]);
+
+    let mut text = Vec::new();
+    v6.serialize(&mut Serializer::new(&mut text).human_readable(true))
+        .unwrap();
+    assert!(to_bytes(&v6).unwrap().len() < text.len());
+}
+
+#[test]
+fn system_time() {
+    round_trip(&SystemTime::UNIX_EPOCH);
+    round_trip(&(SystemTime::UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_789)));
+    round_trip(&Duration::from_millis(1500));
+}
+
+#[test]
+fn human_readable_opt_in() {
+    let addr = Ipv4Addr::new(127, 0, 0, 1);
+
+    let mut bytes = Vec::new();
+    addr.serialize(&mut Serializer::new(&mut bytes).human_readable(true))
+        .unwrap();
+    assert_eq!(from_bytes::<String>(&bytes).unwrap(), "127.0.0.1");
+
+    let mut deserializer = Deserializer::new(bytes.as_slice()).human_readable(true);
+    assert_eq!(Ipv4Addr::deserialize(&mut deserializer).unwrap(), addr);
+
+    // Data written in one mode can't be read in the other
+    assert!(from_bytes::<Ipv4Addr>(&bytes).is_err());
+}
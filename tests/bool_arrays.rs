@@ -0,0 +1,126 @@
+use serde::Serialize;
+use serde_beve::{Deserializer, StreamingSerializer, Writer, from_bytes, to_bytes};
+
+/// Boolean arrays as the reference implementation writes them: the `BOOL_ARRAY` header, the size,
+/// and the booleans packed eight to a byte starting from the most significant bit, with any unused
+/// bits of the last byte left unset.
+fn vectors() -> Vec<(Vec<bool>, Vec<u8>)> {
+    vec![
+        (vec![true], vec![0x1c, 0x04, 0x80]),
+        (vec![true, false, true], vec![0x1c, 0x0c, 0xa0]),
+        (
+            vec![true, false, false, false, false, false, false, true],
+            vec![0x1c, 0x20, 0x81],
+        ),
+        (vec![true; 9], vec![0x1c, 0x24, 0xff, 0x80]),
+        (
+            (0..17).map(|i| i % 2 == 0).collect(),
+            vec![0x1c, 0x44, 0xaa, 0xaa, 0x80],
+        ),
+        (
+            (0..64).map(|i| i % 8 == 7).collect(),
+            // The first size that needs two bytes
+            [[0x1c, 0x01, 0x01].as_slice(), &[0x01; 8]].concat(),
+        ),
+    ]
+}
+
+#[test]
+fn serialized_bit_order() {
+    for (values, expected) in vectors() {
+        assert_eq!(to_bytes(&values).unwrap(), expected);
+
+        let mut streamed = Vec::new();
+        values
+            .serialize(&mut StreamingSerializer::new(&mut streamed))
+            .unwrap();
+        assert_eq!(streamed, expected);
+
+        let mut written = Vec::new();
+        Writer::new(&mut written).write_bool_array(&values).unwrap();
+        assert_eq!(written, expected);
+    }
+}
+
+#[test]
+fn deserialized_bit_order() {
+    for (values, bytes) in vectors() {
+        assert_eq!(from_bytes::<Vec<bool>>(&bytes).unwrap(), values);
+
+        let mut deserializer = Deserializer::new(bytes.as_slice()).strict(true);
+        let out: Vec<bool> = serde::Deserialize::deserialize(&mut deserializer).unwrap();
+        assert_eq!(out, values);
+    }
+}
+
+#[test]
+fn partial_bytes_are_consumed() {
+    for (values, _) in vectors() {
+        let value = (values, 0xabu8, vec![false; 3]);
+        let bytes = to_bytes(&value).unwrap();
+        assert_eq!(
+            from_bytes::<(Vec<bool>, u8, Vec<bool>)>(&bytes).unwrap(),
+            value
+        );
+    }
+}
+
+/// Boolean arrays as versions before 2.0.0 wrote them, packed from the least significant bit.
+fn lsb_first_vectors() -> Vec<(Vec<bool>, Vec<u8>)> {
+    vec![
+        (vec![true], vec![0x1c, 0x04, 0x01]),
+        (vec![true, false, true], vec![0x1c, 0x0c, 0x05]),
+        (vec![true; 9], vec![0x1c, 0x24, 0xff, 0x01]),
+        (
+            (0..17).map(|i| i % 4 == 0).collect(),
+            vec![0x1c, 0x44, 0x11, 0x11, 0x01],
+        ),
+    ]
+}
+
+#[test]
+fn lsb_first_arrays() {
+    for (values, bytes) in lsb_first_vectors() {
+        let mut deserializer = Deserializer::new(bytes.as_slice()).lsb_first_bool_arrays(true);
+        let out: Vec<bool> = serde::Deserialize::deserialize(&mut deserializer).unwrap();
+        assert_eq!(out, values);
+
+        // They don't read back as they were written without it
+        assert_ne!(from_bytes::<Vec<bool>>(&bytes).unwrap(), values);
+    }
+}
+
+#[cfg(feature = "bitvec")]
+#[test]
+fn bitvec_matches_vec_bool() {
+    use bitvec::{order::Lsb0, order::Msb0, vec::BitVec};
+
+    struct Bits<'a>(&'a BitVec<u16, Lsb0>);
+
+    impl Serialize for Bits<'_> {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serde_beve::bitvec::serialize(self.0, serializer)
+        }
+    }
+
+    for (values, expected) in vectors() {
+        let bits: BitVec<u16, Lsb0> = values.iter().copied().collect();
+        assert_eq!(to_bytes(&Bits(&bits)).unwrap(), expected);
+
+        let mut streamed = Vec::new();
+        Bits(&bits)
+            .serialize(&mut StreamingSerializer::new(&mut streamed))
+            .unwrap();
+        assert_eq!(streamed, expected);
+
+        let out: BitVec<u8, Msb0> =
+            serde_beve::bitvec::deserialize(&mut Deserializer::new(expected.as_slice())).unwrap();
+        assert_eq!(out, bits);
+    }
+
+    for (values, bytes) in lsb_first_vectors() {
+        let mut deserializer = Deserializer::new(bytes.as_slice()).lsb_first_bool_arrays(true);
+        let out: BitVec<u8, Msb0> = serde_beve::bitvec::deserialize(&mut deserializer).unwrap();
+        assert_eq!(out, values.iter().copied().collect::<BitVec<u8, Msb0>>());
+    }
+}
@@ -0,0 +1,52 @@
+//! Helpers shared by the integration tests. Not every test uses all of them.
+#![allow(dead_code)]
+
+use serde::{Deserialize, Serialize};
+use serde_beve::{Serializer, StreamingSerializer, from_bytes};
+use std::fmt::Debug;
+
+pub type Configure = fn(Serializer<&mut Vec<u8>>) -> Serializer<&mut Vec<u8>>;
+
+/// Serializes a value with both serializers, configured by `configure`, checks that they agree,
+/// and returns the output.
+pub fn serialize_with<T: ?Sized + Serialize>(value: &T, configure: Configure) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    value
+        .serialize(&mut configure(Serializer::new(&mut bytes)))
+        .unwrap();
+
+    let mut streamed = Vec::new();
+    value
+        .serialize(&mut StreamingSerializer::from(configure(Serializer::new(
+            &mut streamed,
+        ))))
+        .unwrap();
+    assert_eq!(streamed, bytes);
+
+    bytes
+}
+
+/// Serializes a value with both serializers, checks that they agree, and returns the output.
+pub fn serialize<T: ?Sized + Serialize>(value: &T) -> Vec<u8> {
+    serialize_with(value, |s| s)
+}
+
+/// Checks that a value is written the same by both serializers, configured by `configure`, and
+/// reads back, and returns the output.
+pub fn round_trip_with<T>(value: &T, configure: Configure) -> Vec<u8>
+where
+    T: Serialize + for<'de> Deserialize<'de> + PartialEq + Debug,
+{
+    let bytes = serialize_with(value, configure);
+    assert_eq!(&from_bytes::<T>(&bytes).unwrap(), value);
+    bytes
+}
+
+/// Checks that a value is written the same by both serializers and reads back, and returns the
+/// output.
+pub fn round_trip<T>(value: &T) -> Vec<u8>
+where
+    T: Serialize + for<'de> Deserialize<'de> + PartialEq + Debug,
+{
+    round_trip_with(value, |s| s)
+}
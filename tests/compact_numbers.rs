@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+use serde_beve::{Complex, Matrix, Serializer, from_bytes, to_bytes};
+use std::{collections::BTreeMap, fmt::Debug};
+
+/// Serializes the `value` with compact numbers, checks that it reads back, and returns the output.
+fn compact<T>(value: &T) -> Vec<u8>
+where
+    T: Serialize + for<'de> Deserialize<'de> + PartialEq + Debug,
+{
+    let mut bytes = Vec::new();
+    value
+        .serialize(&mut Serializer::new(&mut bytes).compact_numbers(true))
+        .unwrap();
+    assert_eq!(&from_bytes::<T>(&bytes).unwrap(), value);
+    bytes
+}
+
+#[test]
+fn scalars() {
+    assert_eq!(compact(&300u64), [0x31, 0x2c, 0x01]);
+    assert_eq!(compact(&-1i128), [0x09, 0xff]);
+    assert_eq!(compact(&1.5f64), [0x41, 0x00, 0x00, 0xc0, 0x3f]);
+    // Not exactly representable as an `f32`
+    assert_eq!(compact(&0.1f64), to_bytes(&0.1f64).unwrap());
+    compact(&(u64::MAX, i64::MIN, f64::MAX));
+}
+
+#[test]
+fn arrays() {
+    // Narrowed to the widest element
+    assert_eq!(
+        compact(&vec![1u64, 2, 300]),
+        [0x34, 0x0c, 0x01, 0x00, 0x02, 0x00, 0x2c, 0x01]
+    );
+    assert_eq!(compact(&vec![-1i32, 1]), [0x0c, 0x08, 0xff, 0x01]);
+    assert_eq!(compact(&vec![0.5f64, 2.0])[0], 0x44);
+    assert_eq!(compact(&vec![0.5f64, 0.1])[0], 0x64);
+    compact(&Vec::<u64>::new());
+}
+
+#[test]
+fn integer_keys() {
+    let map = BTreeMap::from([(1u64, 2u64), (3, 400)]);
+    let bytes = compact(&map);
+    // Keys keep their width, values are narrowed
+    let mut expected = vec![0x73, 0x08];
+    expected.extend(1u64.to_le_bytes());
+    expected.extend([0x11, 0x02]);
+    expected.extend(3u64.to_le_bytes());
+    expected.extend([0x31, 0x90, 0x01]);
+    assert_eq!(bytes, expected);
+
+    compact(&BTreeMap::from([(-5i16, vec![1.0f64]), (7, vec![])]));
+}
+
+#[test]
+fn nested_special_types() {
+    // The parts of complex numbers are narrowed together
+    let value = vec![Complex::new(1.0f64, -2.0), Complex::new(0.5, 4.0)];
+    assert!(compact(&value).len() < to_bytes(&value).unwrap().len());
+    compact(&Complex::new(1u64, 70000));
+
+    let value = Matrix::layout_right(vec![2, 2], vec![1u64, 2, 3, 4]);
+    assert!(compact(&value).len() < to_bytes(&value).unwrap().len());
+
+    let value = BTreeMap::from([(
+        "series".to_string(),
+        (
+            vec![Complex::new(1.0f64, 0.0)],
+            Matrix::layout_left(vec![1, 2], vec![0.5f64, 0.1]),
+        ),
+    )]);
+    compact(&value);
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use common::round_trip;
+use serde_beve::Complex;
+
+#[test]
+fn single_values() {
+    let bytes = round_trip(&Complex::new(1.5f64, -2.0));
+    assert_eq!(bytes[0], 0x1e);
+    // Not an array
+    assert_eq!(bytes[1] & 1, 0);
+    assert_eq!(bytes.len(), 2 + 16);
+
+    round_trip(&Complex::new(1.5f32, -2.0));
+    round_trip(&Complex::new(-3i8, 4));
+    round_trip(&Complex::new(u64::MAX, 0));
+}
+
+#[test]
+fn arrays() {
+    let values = vec![Complex::new(1.0f64, 2.0), Complex::new(-0.5, 0.0)];
+    let bytes = round_trip(&values);
+    assert_eq!(bytes[0], 0x1e);
+    assert_eq!(bytes[1] & 1, 1);
+    // The size, followed by the parts without any headers of their own
+    assert_eq!(bytes.len(), 2 + 1 + 2 * 16);
+
+    round_trip(&vec![Complex::new(1u16, 2), Complex::new(3, 4)]);
+    round_trip(&Vec::<Complex<f32>>::new());
+    round_trip(&(Complex::new(1i32, 2), vec![Complex::new(0.5f32, 0.25)]));
+}
+
+#[cfg(feature = "num-complex")]
+#[test]
+fn num_complex() {
+    use serde::{Deserialize, Serialize};
+    use serde_beve::to_bytes;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Signal {
+        #[serde(with = "serde_beve::num_complex")]
+        peak: num_complex::Complex<f64>,
+        #[serde(with = "serde_beve::num_complex::vec")]
+        samples: Vec<num_complex::Complex<f64>>,
+    }
+
+    let signal = Signal {
+        peak: num_complex::Complex::new(1.0, -1.0),
+        samples: vec![num_complex::Complex::new(0.5, 0.5); 3],
+    };
+    round_trip(&signal);
+
+    let value = num_complex::Complex::new(1.0f32, 2.0);
+    assert_eq!(
+        to_bytes(&Complex::from(value)).unwrap(),
+        to_bytes(&Complex::new(1.0f32, 2.0)).unwrap()
+    );
+}
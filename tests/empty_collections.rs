@@ -0,0 +1,73 @@
+mod common;
+
+use common::serialize;
+use serde_beve::{F128Bits, Typed, from_bytes, to_bytes};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+
+#[test]
+fn empty_arrays() {
+    assert_eq!(serialize(&Vec::<f64>::new()), [0x05, 0x00]);
+    assert_eq!(serialize(&Typed(Vec::<f64>::new())), [0x64, 0x00]);
+    assert_eq!(serialize(&Typed(Vec::<i8>::new())), [0x0c, 0x00]);
+    assert_eq!(serialize(&Typed(VecDeque::<u16>::new())), [0x34, 0x00]);
+    assert_eq!(serialize(&Typed(BTreeSet::<u128>::new())), [0x94, 0x00]);
+    assert_eq!(serialize(&Typed(&[] as &[f32])), [0x44, 0x00]);
+    assert_eq!(serialize(&Typed(Vec::<F128Bits>::new())), [0x84, 0x00]);
+    assert_eq!(serialize(&Typed(Vec::<bool>::new())), [0x1c, 0x00]);
+    assert_eq!(serialize(&Typed(Vec::<&str>::new())), [0x3c, 0x00]);
+}
+
+#[test]
+fn empty_objects() {
+    assert_eq!(serialize(&HashMap::<u32, String>::new()), [0x03, 0x00]);
+    assert_eq!(
+        serialize(&Typed(HashMap::<u32, String>::new())),
+        [0x53, 0x00]
+    );
+    assert_eq!(
+        serialize(&Typed(BTreeMap::<i64, bool>::new())),
+        [0x6b, 0x00]
+    );
+    assert_eq!(
+        serialize(&Typed(BTreeMap::<String, u8>::new())),
+        [0x03, 0x00]
+    );
+}
+
+#[test]
+fn non_empty_collections_are_unchanged() {
+    let values = vec![1.0f64, 2.0];
+    assert_eq!(serialize(&Typed(&values)), to_bytes(&values).unwrap());
+
+    let map = BTreeMap::from([(1u32, "a"), (2, "b")]);
+    assert_eq!(serialize(&Typed(&map)), to_bytes(&map).unwrap());
+}
+
+#[test]
+fn nested() {
+    // A generic array holding an empty and a non-empty array of `f32`s
+    assert_eq!(
+        serialize(&vec![Typed(Vec::<f32>::new()), Typed(vec![1.0])]),
+        [0x05, 0x08, 0x44, 0x00, 0x44, 0x04, 0x00, 0x00, 0x80, 0x3f],
+    );
+}
+
+#[test]
+fn read_back() {
+    let bytes = to_bytes(&Typed(Vec::<f64>::new())).unwrap();
+    assert_eq!(from_bytes::<Vec<f64>>(&bytes).unwrap(), []);
+    assert_eq!(
+        from_bytes::<Typed<Vec<f64>>>(&bytes).unwrap(),
+        Typed(vec![])
+    );
+
+    let bytes = to_bytes(&Typed(HashMap::<u32, String>::new())).unwrap();
+    assert!(
+        from_bytes::<HashMap<u32, String>>(&bytes)
+            .unwrap()
+            .is_empty()
+    );
+
+    let bytes = to_bytes(&Typed(Vec::<bool>::new())).unwrap();
+    assert!(from_bytes::<Vec<bool>>(&bytes).unwrap().is_empty());
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use common::round_trip_with;
+use serde::{Deserialize, Serialize};
+use serde_beve::{Error, from_bytes, to_bytes};
+use std::fmt::Debug;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+enum Shape {
+    Empty,
+    Circle(f64),
+    Rect(u8, u8),
+    Polygon { sides: u8 },
+}
+
+/// Writes the `value` by name with both serializers, checks that they agree and that it reads
+/// back, and returns the output.
+fn by_name<T>(value: &T) -> Vec<u8>
+where
+    T: Serialize + for<'de> Deserialize<'de> + PartialEq + Debug,
+{
+    round_trip_with(value, |s| s.variants_by_name(true))
+}
+
+#[test]
+fn variants_by_name() {
+    assert_eq!(
+        by_name(&Shape::Empty),
+        [0x02, 0x14, b'E', b'm', b'p', b't', b'y']
+    );
+
+    let bytes = by_name(&Shape::Rect(1, 2));
+    // An object with a single field, keyed by the name of the variant
+    assert_eq!(bytes[..7], [0x03, 0x04, 0x10, b'R', b'e', b'c', b't']);
+
+    by_name(&Shape::Circle(0.5));
+    by_name(&Shape::Polygon { sides: 5 });
+    by_name(&vec![Shape::Empty, Shape::Circle(1.0)]);
+}
+
+#[test]
+fn variants_by_index_still_read() {
+    for shape in [
+        Shape::Empty,
+        Shape::Circle(0.5),
+        Shape::Polygon { sides: 3 },
+    ] {
+        let bytes = to_bytes(&shape).unwrap();
+        assert_eq!(from_bytes::<Shape>(&bytes).unwrap(), shape);
+    }
+}
+
+#[test]
+fn errors_name_the_variant() {
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    enum Other {
+        Circle(String),
+    }
+
+    let bytes = by_name(&Shape::Circle(0.5));
+    let error = from_bytes::<Other>(&bytes).unwrap_err();
+    assert!(matches!(error, Error::InvalidVariant { variant, .. } if variant == "Circle"));
+}
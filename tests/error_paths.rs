@@ -0,0 +1,93 @@
+use serde::{Serialize, ser::SerializeStruct};
+use serde_beve::{Error, Serializer, StreamingSerializer, to_bytes};
+use std::collections::BTreeMap;
+
+struct Route {
+    name: &'static str,
+    // Tuples can't be keys
+    headers: BTreeMap<(u8, u8), u8>,
+}
+
+impl Serialize for Route {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut route = serializer.serialize_struct("Route", 2)?;
+        route.serialize_field("name", &self.name)?;
+        route.serialize_field("headers", &self.headers)?;
+        route.end()
+    }
+}
+
+struct Config {
+    routes: Vec<Route>,
+}
+
+impl Serialize for Config {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut config = serializer.serialize_struct("Config", 1)?;
+        config.serialize_field("routes", &self.routes)?;
+        config.end()
+    }
+}
+
+type Configure = fn(Serializer<&mut Vec<u8>>) -> Serializer<&mut Vec<u8>>;
+
+fn config() -> BTreeMap<&'static str, Config> {
+    let mut routes: Vec<_> = (0..3)
+        .map(|_| Route {
+            name: "ok",
+            headers: BTreeMap::new(),
+        })
+        .collect();
+    routes.push(Route {
+        name: "bad",
+        headers: BTreeMap::from([((1, 2), 3)]),
+    });
+    BTreeMap::from([("config", Config { routes })])
+}
+
+/// Returns the errors of both serializers, configured by `configure`.
+fn errors(value: &impl Serialize, configure: Configure) -> [Error; 2] {
+    let mut out = Vec::new();
+    let buffered = value
+        .serialize(&mut configure(Serializer::new(&mut out)))
+        .unwrap_err();
+
+    let mut out = Vec::new();
+    let streamed = value
+        .serialize(&mut StreamingSerializer::from(configure(Serializer::new(
+            &mut out,
+        ))))
+        .unwrap_err();
+
+    [buffered, streamed]
+}
+
+#[test]
+fn path_to_nested_error() {
+    let configs: [Configure; 3] = [
+        |s| s,
+        |s| s.struct_field_indices(true),
+        |s| s.structs_as_arrays(true),
+    ];
+    for configure in configs {
+        for error in errors(&config(), configure) {
+            assert_eq!(error.path(), Some("config.routes[3].headers"));
+            assert!(matches!(error.without_path(), Error::InvalidKey));
+        }
+    }
+}
+
+#[test]
+fn integer_keys_are_indexed() {
+    let value = BTreeMap::from([(5u8, vec![BTreeMap::from([((1u8, 2u8), 3u8)])])]);
+    for error in errors(&value, |s| s) {
+        assert_eq!(error.path(), Some("[5][0]"));
+    }
+}
+
+#[test]
+fn top_level_errors_have_no_path() {
+    let error = to_bytes(&BTreeMap::from([((1u8, 2u8), 3u8)])).unwrap_err();
+    assert_eq!(error.path(), None);
+    assert!(matches!(error, Error::InvalidKey));
+}
@@ -0,0 +1,123 @@
+use serde::Serialize;
+use serde_beve::{F128Bits, StreamingSerializer, from_bytes, to_bytes};
+
+const ONE: u128 = 0x3fff << 112;
+const SIGN: u128 = 1 << 127;
+const INFINITY: u128 = 0x7fff << 112;
+
+/// The binary128 bits of `2^exponent`.
+const fn power_of_two(exponent: i32) -> u128 {
+    ((exponent + 16383) as u128) << 112
+}
+
+#[test]
+fn exact_conversions() {
+    let values = [
+        (0.0, 0),
+        (-0.0, SIGN),
+        (1.0, ONE),
+        (-2.0, SIGN | power_of_two(1)),
+        (f64::INFINITY, INFINITY),
+        (f64::NEG_INFINITY, SIGN | INFINITY),
+        (f64::MIN_POSITIVE, power_of_two(-1022)),
+        // The smallest subnormal `f64` is normal as a binary128 float
+        (f64::from_bits(1), power_of_two(-1074)),
+        (f64::MAX, power_of_two(1023) | ((1 << 112) - (1 << 60))),
+    ];
+    for (value, bits) in values {
+        assert_eq!(F128Bits::from_f64(value).to_bits(), bits, "{value:e}");
+        assert_eq!(
+            F128Bits::from_bits(bits).to_f64().to_bits(),
+            value.to_bits(),
+            "{value:e}"
+        );
+    }
+}
+
+#[test]
+fn nans() {
+    assert!(F128Bits::from_f64(f64::NAN).to_f64().is_nan());
+    // A payload below the bits an `f64` keeps still leaves a NaN
+    let nan = F128Bits::from_bits(SIGN | INFINITY | 1).to_f64();
+    assert!(nan.is_nan());
+    assert!(nan.is_sign_negative());
+}
+
+#[test]
+fn rounding() {
+    // Halfway between 1 and the next `f64` up rounds to the even 1
+    let half_ulp = 1 << 59;
+    assert_eq!(F128Bits::from_bits(ONE | half_ulp).to_f64(), 1.0);
+    assert_eq!(
+        F128Bits::from_bits(ONE | half_ulp | 1).to_f64(),
+        1.0 + f64::EPSILON
+    );
+    // Halfway between the odd 1 + ε and 1 + 2ε rounds up to the latter
+    assert_eq!(
+        F128Bits::from_bits(ONE | (3 * half_ulp)).to_f64(),
+        1.0 + 2.0 * f64::EPSILON
+    );
+
+    // Likewise for subnormals, where fewer bits are kept
+    let smallest = f64::from_bits(1);
+    assert_eq!(F128Bits::from_bits(power_of_two(-1075)).to_f64(), 0.0);
+    assert_eq!(
+        F128Bits::from_bits(power_of_two(-1075) | 1 << 111).to_f64(),
+        smallest
+    );
+    assert_eq!(
+        F128Bits::from_bits(power_of_two(-1074) | 1 << 111).to_f64(),
+        2.0 * smallest
+    );
+    assert_eq!(F128Bits::from_bits(power_of_two(-1076)).to_f64(), 0.0);
+    // Just below the smallest normal `f64`, rounding carries into the exponent
+    assert_eq!(
+        F128Bits::from_bits(power_of_two(-1022) - 1).to_f64(),
+        f64::MIN_POSITIVE
+    );
+}
+
+#[test]
+fn overflow() {
+    assert_eq!(
+        F128Bits::from_bits(power_of_two(1024)).to_f64(),
+        f64::INFINITY
+    );
+    assert_eq!(
+        F128Bits::from_bits(SIGN | power_of_two(16000)).to_f64(),
+        f64::NEG_INFINITY
+    );
+    // Halfway between `f64::MAX`, whose significand is odd, and the next power of two rounds up
+    let max = F128Bits::from_f64(f64::MAX).to_bits();
+    assert_eq!(F128Bits::from_bits(max | 1 << 59).to_f64(), f64::INFINITY);
+    assert_eq!(
+        F128Bits::from_bits(max | ((1 << 59) - 1)).to_f64(),
+        f64::MAX
+    );
+}
+
+#[test]
+fn headers() {
+    let value = F128Bits::from_f64(-1.5);
+    let bytes = to_bytes(&value).unwrap();
+    assert_eq!(bytes[0], 0x81);
+    assert_eq!(bytes[1..], value.0);
+    assert_eq!(from_bytes::<F128Bits>(&bytes).unwrap(), value);
+
+    let values = vec![
+        F128Bits::from_f64(1.0),
+        F128Bits::from_bits(SIGN | INFINITY),
+    ];
+    let bytes = to_bytes(&values).unwrap();
+    // The array header and its size
+    assert_eq!(bytes[..2], [0x84, 0x08]);
+    assert_eq!(bytes[2..18], values[0].0);
+    assert_eq!(bytes[18..], values[1].0);
+    assert_eq!(from_bytes::<Vec<F128Bits>>(&bytes).unwrap(), values);
+
+    let mut streamed = Vec::new();
+    values
+        .serialize(&mut StreamingSerializer::new(&mut streamed))
+        .unwrap();
+    assert_eq!(streamed, bytes);
+}
@@ -0,0 +1,165 @@
+#![cfg(feature = "half")]
+
+mod common;
+
+use common::{serialize, serialize_with};
+use half::{bf16, f16};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_beve::{TypedArray, from_bytes, to_bytes};
+
+/// Serializes the `value` with both serializers and reads it back. NaNs never compare equal, so
+/// callers check the bits themselves.
+fn read_back<T: Serialize + DeserializeOwned>(value: &T) -> T {
+    from_bytes(&serialize(value)).unwrap()
+}
+
+const F16_BITS: [u16; 7] = [
+    0x3c00, // 1.0
+    0x8000, // -0.0
+    0x0001, // Smallest subnormal
+    0x03ff, // Largest subnormal
+    0x7c00, // Infinity
+    0x7e01, // Quiet NaN with a payload
+    0x7d55, // Signaling NaN with a payload
+];
+
+const BF16_BITS: [u16; 7] = [
+    0x3f80, // 1.0
+    0x8000, // -0.0
+    0x0001, // Smallest subnormal
+    0x007f, // Largest subnormal
+    0x7f80, // Infinity
+    0x7fc1, // Quiet NaN with a payload
+    0x7f95, // Signaling NaN with a payload
+];
+
+#[test]
+fn f16_scalars_are_bit_exact() {
+    for bits in F16_BITS {
+        let value = f16::from_bits(bits);
+        assert_eq!(read_back(&value).to_bits(), bits);
+    }
+}
+
+#[test]
+fn bf16_scalars_are_bit_exact() {
+    for bits in BF16_BITS {
+        let value = bf16::from_bits(bits);
+        assert_eq!(read_back(&value).to_bits(), bits);
+    }
+}
+
+#[test]
+fn f16_arrays_are_bit_exact() {
+    let values: Vec<f16> = F16_BITS.into_iter().map(f16::from_bits).collect();
+    let bytes = to_bytes(&values).unwrap();
+    // Header, size, and two bytes per element
+    assert_eq!(bytes.len(), 2 + 2 * values.len());
+
+    let out = read_back(&values);
+    assert_eq!(
+        out.iter().map(|v| v.to_bits()).collect::<Vec<_>>(),
+        F16_BITS
+    );
+}
+
+#[test]
+fn bf16_arrays_are_bit_exact() {
+    let values: Vec<bf16> = BF16_BITS.into_iter().map(bf16::from_bits).collect();
+    let out = read_back(&values);
+    assert_eq!(
+        out.iter().map(|v| v.to_bits()).collect::<Vec<_>>(),
+        BF16_BITS
+    );
+}
+
+#[test]
+fn mixed_containers() {
+    let value = (f16::from_bits(0x7e01), vec![bf16::from_bits(0x0001)], 3u8);
+    let (a, b, c) = read_back(&value);
+    assert_eq!(a.to_bits(), 0x7e01);
+    assert_eq!(b[0].to_bits(), 0x0001);
+    assert_eq!(c, 3);
+}
+
+#[test]
+fn other_floats_convert() {
+    let bytes = to_bytes(&1.5f32).unwrap();
+    assert_eq!(from_bytes::<f16>(&bytes).unwrap(), f16::from_f32(1.5));
+
+    let bytes = to_bytes(&f16::from_f32(1.5)).unwrap();
+    assert_eq!(from_bytes::<bf16>(&bytes).unwrap(), bf16::from_f32(1.5));
+    assert_eq!(from_bytes::<f32>(&bytes).unwrap(), 1.5);
+}
+
+/// Serializes the `value` canonically with both serializers, checking that they agree.
+fn canonical<T: Serialize>(value: &T) -> Vec<u8> {
+    serialize_with(value, |s| s.canonical(true))
+}
+
+#[test]
+fn canonical_scalars() {
+    for (bits, canonical_bits) in [(0x8000, 0x0000), (0x7e01, 0x7e00), (0x7d55, 0x7e00)] {
+        let bytes = canonical(&f16::from_bits(bits));
+        assert_eq!(from_bytes::<f16>(&bytes).unwrap().to_bits(), canonical_bits);
+    }
+    for (bits, canonical_bits) in [(0x8000, 0x0000), (0x7fc1, 0x7fc0), (0x7f95, 0x7fc0)] {
+        let bytes = canonical(&bf16::from_bits(bits));
+        assert_eq!(
+            from_bytes::<bf16>(&bytes).unwrap().to_bits(),
+            canonical_bits
+        );
+    }
+
+    // Other values are left alone
+    let bytes = canonical(&f16::from_bits(0x0001));
+    assert_eq!(from_bytes::<f16>(&bytes).unwrap().to_bits(), 0x0001);
+}
+
+#[test]
+fn canonical_arrays() {
+    let values: Vec<f16> = [0x8000, 0x7e01, 0x3c00].map(f16::from_bits).to_vec();
+    let expected = [0x0000, 0x7e00, 0x3c00];
+    let bytes = canonical(&values);
+    let read: Vec<u16> = from_bytes::<Vec<f16>>(&bytes)
+        .unwrap()
+        .into_iter()
+        .map(f16::to_bits)
+        .collect();
+    assert_eq!(read, expected);
+
+    let bytes = canonical(&TypedArray(values));
+    let read: Vec<u16> = from_bytes::<TypedArray<f16>>(&bytes)
+        .unwrap()
+        .iter()
+        .map(|v| v.to_bits())
+        .collect();
+    assert_eq!(read, expected);
+
+    let values: Vec<bf16> = [0x8000, 0x7fc1].map(bf16::from_bits).to_vec();
+    let bytes = canonical(&TypedArray(values.clone()));
+    assert_eq!(bytes, canonical(&values));
+    let read: Vec<u16> = from_bytes::<Vec<bf16>>(&bytes)
+        .unwrap()
+        .into_iter()
+        .map(bf16::to_bits)
+        .collect();
+    assert_eq!(read, [0x0000, 0x7fc0]);
+}
+
+#[test]
+fn written_with_their_own_headers() {
+    let value = f16::from_f32(1.0);
+    assert_eq!(to_bytes(&value).unwrap(), [0x21, 0x00, 0x3c]);
+    let value = bf16::from_f32(1.0);
+    assert_eq!(to_bytes(&value).unwrap(), [0x01, 0x80, 0x3f]);
+
+    let values = vec![f16::from_f32(1.0), f16::from_f32(-2.0)];
+    let bytes = to_bytes(&values).unwrap();
+    assert_eq!(bytes, [0x24, 0x08, 0x00, 0x3c, 0x00, 0xc0]);
+    assert_eq!(read_back(&values), values);
+
+    let values = vec![bf16::from_f32(0.5)];
+    assert_eq!(to_bytes(&values).unwrap(), [0x04, 0x04, 0x00, 0x3f]);
+    assert_eq!(read_back(&values), values);
+}
@@ -0,0 +1,149 @@
+mod common;
+
+use common::round_trip;
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{EnumAccess, VariantAccess, Visitor},
+};
+use serde_beve::{Error, StreamingSerializer, to_bytes};
+use std::collections::BTreeMap;
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Color {
+    Red,
+    Green,
+}
+
+impl Serialize for Color {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Color::Red => serializer.serialize_unit_variant("Color", 0, "Red"),
+            Color::Green => serializer.serialize_unit_variant("Color", 1, "Green"),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ColorVisitor;
+
+        impl<'de> Visitor<'de> for ColorVisitor {
+            type Value = Color;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a color")
+            }
+
+            fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Color, A::Error> {
+                let (name, variant) = data.variant::<String>()?;
+                variant.unit_variant()?;
+                match name.as_str() {
+                    "Red" => Ok(Color::Red),
+                    "Green" => Ok(Color::Green),
+                    _ => Err(serde::de::Error::unknown_variant(&name, &["Red", "Green"])),
+                }
+            }
+        }
+
+        deserializer.deserialize_enum("Color", &["Red", "Green"], ColorVisitor)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct UserId(u64);
+
+impl Serialize for UserId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct("UserId", &self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for UserId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct UserIdVisitor;
+
+        impl<'de> Visitor<'de> for UserIdVisitor {
+            type Value = UserId;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a user ID")
+            }
+
+            fn visit_newtype_struct<D: Deserializer<'de>>(
+                self,
+                deserializer: D,
+            ) -> Result<UserId, D::Error> {
+                u64::deserialize(deserializer).map(UserId)
+            }
+        }
+
+        deserializer.deserialize_newtype_struct("UserId", UserIdVisitor)
+    }
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct Paint(Color);
+
+impl Serialize for Paint {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct("Paint", &self.0)
+    }
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct Sides(u8);
+
+impl Serialize for Sides {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_variant("Shape", 0, "Sides", &self.0)
+    }
+}
+
+#[test]
+fn unit_variants_are_keyed_by_name() {
+    assert_eq!(
+        round_trip(&BTreeMap::from([(Color::Red, 1u8), (Color::Green, 2)])),
+        [
+            0x03, 0x08, 0x0c, b'R', b'e', b'd', 0x11, 0x01, 0x14, b'G', b'r', b'e', b'e', b'n',
+            0x11, 0x02,
+        ],
+    );
+}
+
+#[test]
+fn chars_are_string_keys() {
+    assert_eq!(
+        round_trip(&BTreeMap::from([('a', 1u8), ('é', 2)])),
+        [
+            0x03, 0x08, 0x04, b'a', 0x11, 0x01, 0x08, 0xc3, 0xa9, 0x11, 0x02,
+        ],
+    );
+}
+
+#[test]
+fn newtypes_are_keyed_by_their_contents() {
+    let mut expected = vec![0x73, 0x04];
+    expected.extend(7u64.to_le_bytes());
+    expected.extend([0x11, 0x01]);
+    assert_eq!(round_trip(&BTreeMap::from([(UserId(7), 1u8)])), expected);
+
+    let map = BTreeMap::from([(Paint(Color::Red), 1u8)]);
+    assert_eq!(
+        to_bytes(&map).unwrap(),
+        [0x03, 0x04, 0x0c, b'R', b'e', b'd', 0x11, 0x01]
+    );
+}
+
+#[test]
+fn other_keys_are_rejected() {
+    let map = BTreeMap::from([(Sides(3), 1u8)]);
+    assert!(matches!(to_bytes(&map), Err(Error::InvalidKey)));
+    let mut streamed = Vec::new();
+    assert!(matches!(
+        map.serialize(&mut StreamingSerializer::new(&mut streamed)),
+        Err(Error::InvalidKey)
+    ));
+
+    let map = BTreeMap::from([(true, 1u8)]);
+    assert!(matches!(to_bytes(&map), Err(Error::InvalidKey)));
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use common::round_trip;
+use serde_beve::{Complex, Matrix, MatrixLayout, from_bytes};
+
+#[test]
+fn layouts() {
+    let matrix = Matrix::layout_right(vec![2, 3], vec![1u8, 2, 3, 4, 5, 6]);
+    let bytes = round_trip(&matrix);
+    // The matrix header, followed by the layout bit
+    assert_eq!(bytes[..2], [0x16, 0x00]);
+
+    let matrix = Matrix::layout_left(vec![3, 2], vec![1u8, 2, 3, 4, 5, 6]);
+    let bytes = round_trip(&matrix);
+    assert_eq!(bytes[..2], [0x16, 0x01]);
+    assert_eq!(
+        from_bytes::<Matrix<u8>>(&bytes).unwrap().layout,
+        MatrixLayout::Left
+    );
+}
+
+#[test]
+fn element_types() {
+    round_trip(&Matrix::layout_right(
+        vec![2, 2],
+        vec![1.5f32, -2.0, 0.0, 4.0],
+    ));
+    round_trip(&Matrix::layout_left(vec![1, 3], vec![0.1f64, 0.2, 0.3]));
+    round_trip(&Matrix::layout_right(vec![2], vec![-1i64, i64::MAX]));
+    round_trip(&Matrix::layout_right(
+        vec![1, 2],
+        vec![Complex::new(1.0f64, -1.0), Complex::new(0.5, 0.5)],
+    ));
+}
+
+#[test]
+fn nested() {
+    let value = vec![
+        Matrix::layout_right(vec![1, 1], vec![1u32]),
+        Matrix::layout_right(vec![1, 2], vec![2, 3]),
+    ];
+    round_trip(&value);
+}
@@ -0,0 +1,48 @@
+use serde::Serialize;
+use serde_beve::{Serializer, from_bytes};
+
+/// A number from a loosely typed source, serialized in the width it was given in.
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Num {
+    Short(i16),
+    Int(i32),
+    Unsigned(u8),
+    Float(f32),
+    Double(f64),
+}
+
+fn promoted<T: Serialize>(value: &T) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    value
+        .serialize(&mut Serializer::new(&mut bytes).promote_numeric_arrays(true))
+        .unwrap();
+    bytes
+}
+
+#[test]
+fn widest_integer() {
+    let bytes = promoted(&[Num::Short(-1), Num::Int(70000), Num::Short(2)]);
+    // An array of `i32`s
+    assert_eq!(bytes[0], 0x4c);
+    assert_eq!(from_bytes::<Vec<i32>>(&bytes).unwrap(), [-1, 70000, 2]);
+}
+
+#[test]
+fn widest_float() {
+    let bytes = promoted(&vec![Num::Float(0.5), Num::Double(0.1)]);
+    // An array of `f64`s
+    assert_eq!(bytes[0], 0x64);
+    assert_eq!(from_bytes::<Vec<f64>>(&bytes).unwrap(), [0.5, 0.1]);
+}
+
+#[test]
+fn mixed_kinds_stay_generic() {
+    let bytes = promoted(&vec![Num::Short(-1), Num::Unsigned(1)]);
+    assert_eq!(bytes[0], 0x05);
+    assert_eq!(from_bytes::<(i16, u8)>(&bytes).unwrap(), (-1, 1));
+
+    let bytes = promoted(&vec![Num::Int(1), Num::Double(1.5)]);
+    assert_eq!(bytes[0], 0x05);
+    assert_eq!(from_bytes::<(i32, f64)>(&bytes).unwrap(), (1, 1.5));
+}
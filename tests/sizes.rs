@@ -0,0 +1,144 @@
+use serde::Serialize;
+use serde_beve::{
+    Complex, F128Bits, Matrix, Serializer, StreamingSerializer, serialized_size, to_bytes,
+};
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Serialize)]
+struct Reading {
+    sensor: String,
+    values: Vec<u64>,
+    scale: f64,
+    offset: Option<i32>,
+}
+
+fn reading() -> Reading {
+    Reading {
+        sensor: "thermometer".to_string(),
+        values: vec![1, 2, 300],
+        scale: 0.5,
+        offset: None,
+    }
+}
+
+#[derive(Serialize)]
+enum Shape {
+    Empty,
+    Circle(f32),
+    Rect { width: u16, height: u16 },
+    Path(Vec<(i8, i8)>),
+}
+
+/// Checks that the size of the `value` is that of its output.
+fn check_size(value: &impl Serialize) {
+    assert_eq!(
+        serialized_size(value).unwrap(),
+        to_bytes(value).unwrap().len() as u64
+    );
+}
+
+type Configure = fn(Serializer<&mut Vec<u8>>) -> Serializer<&mut Vec<u8>>;
+
+#[test]
+fn default_options() {
+    check_size(&(reading(), HashMap::from([(1u8, "a")])));
+}
+
+#[test]
+fn with_options() {
+    let configs: [Configure; 4] = [
+        |s| s.compact_numbers(true),
+        |s| s.structs_as_arrays(true),
+        |s| s.struct_field_indices(true),
+        |s| s.canonical(true).compact_numbers(true),
+    ];
+    for configure in configs {
+        let mut out = Vec::new();
+        let serializer = configure(Serializer::new(&mut out));
+        let size = serializer.serialized_size(&reading()).unwrap();
+        reading()
+            .serialize(&mut StreamingSerializer::from(serializer))
+            .unwrap();
+        assert_eq!(size, out.len() as u64);
+        assert_ne!(size, serialized_size(&reading()).unwrap());
+    }
+}
+
+#[test]
+fn extension_types() {
+    check_size(&F128Bits::from(1.5));
+    check_size(&vec![F128Bits::from(1.5), F128Bits::from(-0.0)]);
+    check_size(&Complex::new(1.5f64, -2.0));
+    check_size(&vec![Complex::new(1u8, 2), Complex::new(3, 4)]);
+    check_size(&Matrix::layout_right(vec![2, 3], vec![1u16, 2, 3, 4, 5, 6]));
+    check_size(&Matrix::layout_left(
+        vec![2],
+        vec![Complex::new(1.0f32, 0.0), Complex::new(0.0, 1.0)],
+    ));
+}
+
+#[test]
+fn bool_arrays() {
+    for len in [0, 1, 7, 8, 9, 16, 100] {
+        check_size(&vec![true; len]);
+    }
+}
+
+#[cfg(feature = "half")]
+#[test]
+fn half_floats() {
+    use half::{bf16, f16};
+
+    check_size(&f16::from_f32(1.5));
+    check_size(&bf16::from_f32(1.5));
+    check_size(&vec![f16::from_f32(1.5); 3]);
+    check_size(&vec![bf16::from_f32(1.5); 3]);
+}
+
+#[test]
+fn tags() {
+    let shapes = vec![
+        Shape::Empty,
+        Shape::Circle(1.5),
+        Shape::Rect {
+            width: 2,
+            height: 3,
+        },
+        Shape::Path(vec![(0, 0), (1, -1)]),
+    ];
+    check_size(&shapes);
+
+    let serializer = Serializer::new(std::io::sink()).variants_by_name(true);
+    let mut out = Vec::new();
+    shapes
+        .serialize(&mut Serializer::new(&mut out).variants_by_name(true))
+        .unwrap();
+    assert_eq!(
+        serializer.serialized_size(&shapes).unwrap(),
+        out.len() as u64
+    );
+}
+
+#[test]
+fn string_arrays() {
+    check_size(&Vec::<String>::new());
+    check_size(&vec!["a", "", "longer string"]);
+}
+
+#[test]
+fn integer_keys() {
+    check_size(&BTreeMap::from([(1u16, "a"), (300, "b")]));
+    check_size(&BTreeMap::from([(-1i64, vec![1u8]), (5, vec![])]));
+    check_size(&BTreeMap::from([(u128::MAX, ())]));
+}
+
+#[test]
+fn size_boundaries() {
+    // Sizes take 1 byte below 64, 2 below 16384, and 4 below 2^30
+    for len in [63, 64, 16383, 16384] {
+        check_size(&"a".repeat(len));
+        check_size(&vec![0u32; len]);
+        check_size(&vec![(); len]);
+        check_size(&vec![true; len * 8]);
+    }
+}
@@ -0,0 +1,117 @@
+use serde::Serialize;
+use serde_beve::{Error, to_bytes, to_slice};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    collections::BTreeMap,
+};
+
+/// Counts the allocations of each thread, so tests running in parallel don't interfere.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Returns the number of allocations made by `f`.
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+fn value() -> BTreeMap<&'static str, Vec<u32>> {
+    BTreeMap::from([("a", vec![1, 2, 3]), ("b", vec![])])
+}
+
+#[test]
+fn exact_fit() {
+    let expected = to_bytes(&value()).unwrap();
+    let mut buf = vec![0; expected.len()];
+    assert_eq!(to_slice(&mut buf, &value()).unwrap(), expected.len());
+    assert_eq!(buf, expected);
+}
+
+#[test]
+fn larger_buffer() {
+    let expected = to_bytes(&value()).unwrap();
+    let mut buf = vec![0xff; expected.len() + 8];
+    assert_eq!(to_slice(&mut buf, &value()).unwrap(), expected.len());
+    assert_eq!(buf[..expected.len()], expected);
+    assert!(buf[expected.len()..].iter().all(|b| *b == 0xff));
+}
+
+#[test]
+fn buffer_full() {
+    let needed = to_bytes(&value()).unwrap().len();
+    let mut buf = vec![0; needed - 1];
+    assert!(matches!(
+        to_slice(&mut buf, &value()),
+        Err(Error::BufferFull { needed: Some(n) }) if n == needed
+    ));
+}
+
+#[test]
+fn empty_buffer() {
+    assert!(matches!(
+        to_slice(&mut [], &1u8),
+        Err(Error::BufferFull { needed: Some(2) })
+    ));
+    assert!(matches!(
+        to_slice(&mut [], &()),
+        Err(Error::BufferFull { needed: Some(1) })
+    ));
+}
+
+#[derive(Serialize)]
+enum Unit {
+    Celsius,
+    Scaled { factor: f64 },
+}
+
+#[derive(Serialize)]
+struct Reading {
+    sensor: String,
+    values: Vec<u32>,
+    labels: BTreeMap<String, Option<i16>>,
+    unit: Unit,
+    units: Vec<Unit>,
+    symbol: char,
+}
+
+#[test]
+fn no_allocations() {
+    let value = Reading {
+        sensor: "thermometer".to_string(),
+        values: vec![1, 2, 300],
+        labels: BTreeMap::from([("a".to_string(), Some(-1)), ("b".to_string(), None)]),
+        unit: Unit::Celsius,
+        units: vec![Unit::Scaled { factor: 0.5 }, Unit::Celsius],
+        symbol: '°',
+    };
+    let expected = to_bytes(&value).unwrap();
+    let mut buf = [0; 256];
+
+    let mut written = 0;
+    let count = allocations(|| written = to_slice(&mut buf, &value).unwrap());
+    assert_eq!(count, 0);
+    assert_eq!(buf[..written], expected);
+
+    // Nor when the buffer is too small
+    let count = allocations(|| assert!(to_slice(&mut buf[..8], &value).is_err()));
+    assert_eq!(count, 0);
+}
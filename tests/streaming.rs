@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+use serde_beve::{Error, Serializer, StreamingSerializer, from_bytes, to_bytes};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Debug,
+};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Sample {
+    id: u32,
+    name: String,
+    scores: Vec<f64>,
+    tags: BTreeMap<String, i16>,
+    parent: Option<u64>,
+    pair: (u8, String),
+}
+
+fn sample() -> Sample {
+    Sample {
+        id: 7,
+        name: "sample".to_string(),
+        scores: vec![1.0, 2.5, -0.0],
+        tags: BTreeMap::from([("a".to_string(), -1), ("b".to_string(), 300)]),
+        parent: None,
+        pair: (1, "one".to_string()),
+    }
+}
+
+type Configure = fn(Serializer<&mut Vec<u8>>) -> Serializer<&mut Vec<u8>>;
+
+/// Returns the output of both serializers, configured by `configure`.
+fn outputs(value: &impl Serialize, configure: Configure) -> [Vec<u8>; 2] {
+    let mut buffered = Vec::new();
+    value
+        .serialize(&mut configure(Serializer::new(&mut buffered)))
+        .unwrap();
+
+    let mut streamed = Vec::new();
+    value
+        .serialize(&mut StreamingSerializer::from(configure(Serializer::new(
+            &mut streamed,
+        ))))
+        .unwrap();
+
+    [buffered, streamed]
+}
+
+/// Checks that the output of both serializers reads back into `value`.
+fn reads_back<T>(value: &T, configure: Configure)
+where
+    T: Serialize + for<'de> Deserialize<'de> + PartialEq + Debug,
+{
+    for bytes in outputs(value, configure) {
+        assert_eq!(&from_bytes::<T>(&bytes).unwrap(), value);
+    }
+}
+
+#[test]
+fn same_output() {
+    let configs: [Configure; 4] = [
+        |s| s,
+        |s| s.struct_field_indices(true),
+        |s| s.structs_as_arrays(true),
+        |s| s.variants_by_name(true),
+    ];
+    for configure in configs {
+        let [buffered, streamed] = outputs(&sample(), configure);
+        assert_eq!(buffered, streamed);
+        reads_back(&sample(), configure);
+    }
+
+    let [buffered, streamed] = outputs(&vec![sample(), sample()], |s| s);
+    assert_eq!(buffered, streamed);
+}
+
+#[test]
+fn optional_elements() {
+    let values = vec![Some(1u32), Some(2)];
+    let [buffered, streamed] = outputs(&values, |s| s);
+    // A typed array when buffered, a generic one when streamed
+    assert_eq!(buffered[0], 0x54);
+    assert_eq!(streamed[0], 0x05);
+    reads_back(&values, |s| s);
+}
+
+#[test]
+fn compact_arrays() {
+    let values = vec![1u64, 2];
+    let [buffered, streamed] = outputs(&values, |s| s.compact_numbers(true));
+    assert_eq!(buffered, [0x14, 0x08, 0x01, 0x02]);
+    assert_eq!(streamed, to_bytes(&values).unwrap());
+    reads_back(&values, |s| s.compact_numbers(true));
+
+    // Numbers on their own are narrowed either way
+    let [buffered, streamed] = outputs(&5u64, |s| s.compact_numbers(true));
+    assert_eq!(buffered, streamed);
+}
+
+#[test]
+fn unsorted_keys() {
+    let map: HashMap<u32, u8> = (0..32).map(|i| (i, i as u8)).collect();
+    let sorted: BTreeMap<u32, u8> = map.iter().map(|(k, v)| (*k, *v)).collect();
+    let [buffered, _] = outputs(&map, |s| s.canonical(true));
+    assert_eq!(buffered, to_bytes(&sorted).unwrap());
+    reads_back(&map, |s| s.canonical(true));
+    reads_back(&sample(), |s| s.canonical(true));
+
+    // Maps serialized in order are still canonical
+    let [buffered, streamed] = outputs(&sorted, |s| s.canonical(true));
+    assert_eq!(buffered, streamed);
+}
+
+#[test]
+fn none_fields() {
+    let configure: Configure = |s| s.skip_none_fields(true);
+    let [buffered, streamed] = outputs(&sample(), configure);
+    assert!(buffered.len() < streamed.len());
+    reads_back(&sample(), configure);
+
+    let configure: Configure = |s| s.skip_none_fields(true).struct_field_indices(true);
+    reads_back(&sample(), configure);
+}
+
+#[test]
+fn mixed_elements() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(untagged)]
+    enum Field {
+        Int(i32),
+        Text(String),
+    }
+
+    let values = vec![Field::Int(1), Field::Text("a".to_string())];
+    let buffered = to_bytes(&values).unwrap();
+    assert_eq!(buffered[0], 0x05);
+    assert_eq!(from_bytes::<Vec<Field>>(&buffered).unwrap(), values);
+
+    // Untagged variants are serialized as their bare contents, so the first element looks like
+    // the start of an array of `i32`s. Its header is already written by the time the string
+    // arrives, so the streaming serializer can't fall back to a generic array.
+    let error = values
+        .serialize(&mut StreamingSerializer::new(Vec::new()))
+        .unwrap_err();
+    assert_eq!(error.path(), Some("[1]"));
+    assert!(matches!(
+        error.without_path(),
+        Error::MismatchedElementType { .. }
+    ));
+}
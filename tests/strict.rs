@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+use serde_beve::{Deserializer, Error, Serializer, error::Violation};
+use std::collections::{BTreeMap, HashMap};
+
+fn strict<'de, T: Deserialize<'de>>(bytes: &[u8]) -> Result<T, Error> {
+    T::deserialize(&mut Deserializer::new(bytes).strict(true))
+}
+
+fn violation<T: for<'de> Deserialize<'de>>(bytes: &[u8]) -> Violation {
+    match strict::<T>(bytes) {
+        Err(Error::NonCanonical(violation)) => violation,
+        other => panic!("expected a violation, got {:?}", other.err()),
+    }
+}
+
+#[test]
+fn canonical_output_reads_back() {
+    let map: HashMap<String, Vec<f64>> = (0..10)
+        .map(|i| (i.to_string(), vec![i as f64, -0.0, f64::NAN]))
+        .collect();
+    let mut bytes = Vec::new();
+    map.serialize(&mut Serializer::new(&mut bytes).canonical(true))
+        .unwrap();
+
+    let read: HashMap<String, Vec<f64>> = strict(&bytes).unwrap();
+    assert_eq!(read.len(), map.len());
+    assert!(read["3"][2].is_nan());
+}
+
+#[test]
+fn oversized_sizes() {
+    // "abc" with its size in two bytes
+    let bytes = [0x02, 0x0d, 0x00, b'a', b'b', b'c'];
+    assert!(matches!(
+        violation::<String>(&bytes),
+        Violation::OversizedSize
+    ));
+    assert_eq!(serde_beve::from_bytes::<String>(&bytes).unwrap(), "abc");
+}
+
+#[test]
+fn unsorted_and_duplicate_keys() {
+    let unsorted = [0x03, 0x08, 0x04, b'b', 0x11, 0x01, 0x04, b'a', 0x11, 0x02];
+    assert!(matches!(
+        violation::<BTreeMap<String, u8>>(&unsorted),
+        Violation::UnsortedKeys
+    ));
+
+    let duplicate = [0x03, 0x08, 0x04, b'a', 0x11, 0x01, 0x04, b'a', 0x11, 0x02];
+    assert!(matches!(
+        violation::<BTreeMap<String, u8>>(&duplicate),
+        Violation::DuplicateKey
+    ));
+}
+
+#[test]
+fn non_canonical_floats() {
+    let mut negative_zero = vec![0x61];
+    negative_zero.extend((-0.0f64).to_le_bytes());
+    assert!(matches!(
+        violation::<f64>(&negative_zero),
+        Violation::NonCanonicalFloat
+    ));
+
+    let mut nan = vec![0x41];
+    nan.extend(f32::from_bits(0x7fc0_0001).to_le_bytes());
+    assert!(matches!(
+        violation::<f32>(&nan),
+        Violation::NonCanonicalFloat
+    ));
+}
+
+#[cfg(feature = "half")]
+#[test]
+fn non_canonical_half_floats() {
+    use half::{bf16, f16};
+    use serde_beve::TypedArray;
+
+    // A quiet NaN with a payload
+    let nan = [0x21, 0x01, 0x7e];
+    assert!(matches!(
+        violation::<f16>(&nan),
+        Violation::NonCanonicalFloat
+    ));
+    assert!(matches!(
+        violation::<f32>(&nan),
+        Violation::NonCanonicalFloat
+    ));
+    assert!(strict::<f16>(&[0x21, 0x00, 0x7e]).unwrap().is_nan());
+
+    let negative_zero = [0x01, 0x00, 0x80];
+    assert!(matches!(
+        violation::<bf16>(&negative_zero),
+        Violation::NonCanonicalFloat
+    ));
+    assert!(matches!(
+        violation::<f64>(&negative_zero),
+        Violation::NonCanonicalFloat
+    ));
+
+    let nans = [0x24, 0x08, 0x00, 0x7e, 0x01, 0x7e];
+    assert!(matches!(
+        violation::<Vec<f16>>(&nans),
+        Violation::NonCanonicalFloat
+    ));
+    assert!(matches!(
+        violation::<TypedArray<f16>>(&nans),
+        Violation::NonCanonicalFloat
+    ));
+    let negative_zeros = [0x04, 0x04, 0x00, 0x80];
+    assert!(matches!(
+        violation::<TypedArray<bf16>>(&negative_zeros),
+        Violation::NonCanonicalFloat
+    ));
+
+    let values = vec![f16::NAN, f16::NEG_ZERO, f16::from_f32(1.5)];
+    let mut bytes = Vec::new();
+    values
+        .serialize(&mut Serializer::new(&mut bytes).canonical(true))
+        .unwrap();
+    let read: TypedArray<f16> = strict(&bytes).unwrap();
+    assert!(read[0].is_nan());
+    assert_eq!(read[1..], [f16::ZERO, f16::from_f32(1.5)]);
+}
+
+#[test]
+fn bool_array_padding() {
+    assert!(matches!(
+        violation::<Vec<bool>>(&[0x1c, 0x04, 0xff]),
+        Violation::BoolArrayPadding
+    ));
+    assert!(matches!(
+        violation::<Vec<bool>>(&[0x1c, 0x24, 0xff, 0xc0]),
+        Violation::BoolArrayPadding
+    ));
+    assert_eq!(
+        serde_beve::from_bytes::<Vec<bool>>(&[0x1c, 0x04, 0xff]).unwrap(),
+        [true]
+    );
+    assert_eq!(strict::<Vec<bool>>(&[0x1c, 0x04, 0x80]).unwrap(), [true]);
+    assert_eq!(strict::<Vec<bool>>(&[0x1c, 0x20, 0xff]).unwrap(), [true; 8]);
+}
+
+#[cfg(feature = "bitvec")]
+#[test]
+fn bitvec_padding() {
+    use bitvec::vec::BitVec;
+
+    let deserialize = |bytes: &[u8]| -> Result<BitVec<u8>, Error> {
+        serde_beve::bitvec::deserialize(&mut Deserializer::new(bytes).strict(true))
+    };
+    assert!(matches!(
+        deserialize(&[0x1c, 0x0c, 0xbf]),
+        Err(Error::NonCanonical(Violation::BoolArrayPadding))
+    ));
+    assert_eq!(deserialize(&[0x1c, 0x0c, 0xa0]).unwrap().len(), 3);
+}
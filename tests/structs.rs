@@ -0,0 +1,158 @@
+use serde::{Deserialize, Serialize};
+use serde_beve::{Error, Serializer, StreamingSerializer, from_bytes, to_bytes};
+use std::fmt::Debug;
+
+type Configure = fn(Serializer<&mut Vec<u8>>) -> Serializer<&mut Vec<u8>>;
+
+/// Serializes a value with both serializers, configured by `configure`, checking that they agree.
+fn serialize<T: Serialize>(value: &T, configure: Configure) -> Result<Vec<u8>, Error> {
+    let mut buffered = Vec::new();
+    let result = value.serialize(&mut configure(Serializer::new(&mut buffered)));
+
+    let mut streamed = Vec::new();
+    let streamed_result = value.serialize(&mut StreamingSerializer::from(configure(
+        Serializer::new(&mut streamed),
+    )));
+    assert_eq!(result.is_ok(), streamed_result.is_ok());
+
+    result?;
+    assert_eq!(buffered, streamed);
+    Ok(buffered)
+}
+
+/// Serializes a value with a [`Serializer`] alone, configured by `configure`, and checks that it
+/// reads back.
+fn buffered_round_trip<T>(value: &T, configure: Configure) -> Vec<u8>
+where
+    T: Serialize + for<'de> Deserialize<'de> + PartialEq + Debug,
+{
+    let mut bytes = Vec::new();
+    value
+        .serialize(&mut configure(Serializer::new(&mut bytes)))
+        .unwrap();
+    assert_eq!(&from_bytes::<T>(&bytes).unwrap(), value);
+    bytes
+}
+
+/// Checks that a value reads back after being written with `configure`.
+fn round_trip<T>(value: &T, configure: Configure) -> Vec<u8>
+where
+    T: Serialize + for<'de> Deserialize<'de> + PartialEq + Debug,
+{
+    let bytes = serialize(value, configure).unwrap();
+    assert_eq!(&from_bytes::<T>(&bytes).unwrap(), value);
+    bytes
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Record {
+    a: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    b: Option<u32>,
+    c: String,
+}
+
+#[test]
+fn positional() {
+    let record = Record {
+        a: 1,
+        b: Some(2),
+        c: "x".into(),
+    };
+    let bytes = round_trip(&record, |s| s.structs_as_arrays(true));
+    // A generic array of the three fields
+    assert_eq!(
+        bytes,
+        [
+            0x05, 0x0c, 0x51, 0x01, 0x00, 0x00, 0x00, 0x51, 0x02, 0x00, 0x00, 0x00, 0x02, 0x04,
+            b'x'
+        ]
+    );
+}
+
+#[test]
+fn positional_fields_cannot_be_skipped() {
+    let record = Record {
+        a: 1,
+        b: None,
+        c: "x".into(),
+    };
+    let error = serialize(&record, |s| s.structs_as_arrays(true)).unwrap_err();
+    assert!(matches!(error, Error::SkippedField("b")));
+
+    // Skipping is fine when fields are named
+    round_trip(&record, |s| s);
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Sparse {
+    a: Option<u32>,
+    b: u8,
+    c: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+enum Event {
+    Moved { x: Option<i8>, y: Option<i8> },
+}
+
+#[test]
+fn none_fields_are_skipped() {
+    let sparse = Sparse {
+        a: None,
+        b: 1,
+        c: None,
+    };
+    let bytes = buffered_round_trip(&sparse, |s| s.skip_none_fields(true));
+    // An object with only the field `b`
+    assert_eq!(bytes, [0x03, 0x04, 0x04, b'b', 0x11, 0x01]);
+
+    let sparse = Sparse {
+        a: Some(2),
+        b: 1,
+        c: None,
+    };
+    buffered_round_trip(&sparse, |s| s.skip_none_fields(true));
+
+    let event = Event::Moved {
+        x: Some(1),
+        y: None,
+    };
+    let bytes = buffered_round_trip(&event, |s| s.skip_none_fields(true));
+    assert!(bytes.len() < to_bytes(&event).unwrap().len());
+    buffered_round_trip(&event, |s| s.skip_none_fields(true).variants_by_name(true));
+}
+
+#[test]
+fn field_indices() {
+    let record = Record {
+        a: 1,
+        b: Some(2),
+        c: "x".into(),
+    };
+    let bytes = round_trip(&record, |s| s.struct_field_indices(true));
+    // A `u8`-keyed object
+    assert_eq!(
+        bytes,
+        [
+            0x13, 0x0c, 0x00, 0x51, 0x01, 0x00, 0x00, 0x00, 0x01, 0x51, 0x02, 0x00, 0x00, 0x00,
+            0x02, 0x02, 0x04, b'x'
+        ]
+    );
+
+    // Skipped fields keep the indices of the following ones
+    let record = Record {
+        a: 1,
+        b: None,
+        c: "x".into(),
+    };
+    let bytes = round_trip(&record, |s| s.struct_field_indices(true));
+    assert_eq!(bytes[..3], [0x13, 0x08, 0x00]);
+    assert_eq!(bytes[8], 0x02);
+
+    let event = Event::Moved {
+        x: Some(1),
+        y: Some(-1),
+    };
+    round_trip(&event, |s| s.struct_field_indices(true));
+}
@@ -0,0 +1,254 @@
+use serde::{
+    Deserialize, Serialize,
+    de::{IntoDeserializer, Visitor},
+};
+use serde_beve::{
+    Deserializer, Error, Number, Serializer, StreamingSerializer, TypedArray, TypedSlice,
+    from_bytes, to_bytes,
+};
+use std::fmt::Debug;
+
+/// Checks that a typed array is written exactly like the [`Vec`] it wraps, and reads back.
+fn matches_vec<T>(values: Vec<T>)
+where
+    T: Number + Serialize + for<'de> Deserialize<'de> + PartialEq + Debug,
+{
+    let expected = to_bytes(&values).unwrap();
+    assert_eq!(to_bytes(&TypedSlice(&values)).unwrap(), expected);
+
+    let array = TypedArray(values);
+    assert_eq!(to_bytes(&array).unwrap(), expected);
+
+    let mut streamed = Vec::new();
+    array
+        .serialize(&mut StreamingSerializer::new(&mut streamed))
+        .unwrap();
+    assert_eq!(streamed, expected);
+
+    assert_eq!(from_bytes::<TypedArray<T>>(&expected).unwrap(), array);
+}
+
+#[test]
+fn every_number_type() {
+    matches_vec(vec![1i8, -2, i8::MIN]);
+    matches_vec(vec![1i16, -2, i16::MIN]);
+    matches_vec(vec![1i32, -2, i32::MIN]);
+    matches_vec(vec![1i64, -2, i64::MIN]);
+    matches_vec(vec![1i128, -2, i128::MIN]);
+    matches_vec(vec![1u8, 2, u8::MAX]);
+    matches_vec(vec![1u16, 2, u16::MAX]);
+    matches_vec(vec![1u32, 2, u32::MAX]);
+    matches_vec(vec![1u64, 2, u64::MAX]);
+    matches_vec(vec![1u128, 2, u128::MAX]);
+    matches_vec(vec![1.5f32, -0.25, f32::MAX]);
+    matches_vec(vec![1.5f64, -0.25, f64::MAX]);
+}
+
+#[test]
+fn nested() {
+    let value = (
+        vec![TypedArray(vec![1.0f64, 2.0]), TypedArray(vec![3.0])],
+        4u8,
+    );
+    let bytes = to_bytes(&value).unwrap();
+    assert_eq!(
+        bytes,
+        to_bytes(&(vec![vec![1.0f64, 2.0], vec![3.0]], 4u8)).unwrap()
+    );
+
+    let mut streamed = Vec::new();
+    value
+        .serialize(&mut StreamingSerializer::new(&mut streamed))
+        .unwrap();
+    assert_eq!(streamed, bytes);
+
+    assert_eq!(
+        from_bytes::<(Vec<TypedArray<f64>>, u8)>(&bytes).unwrap(),
+        value
+    );
+}
+
+#[test]
+fn never_narrowed() {
+    let array = TypedArray(vec![1u64, 2, 3]);
+    let mut bytes = Vec::new();
+    array
+        .serialize(&mut Serializer::new(&mut bytes).compact_numbers(true))
+        .unwrap();
+    assert_eq!(bytes, to_bytes(&array).unwrap());
+}
+
+#[test]
+fn never_narrowed_when_nested() {
+    let value = (
+        vec![TypedArray(vec![1u64, 2, 3])],
+        TypedArray(vec![4i32]),
+        5u64,
+    );
+    let mut bytes = Vec::new();
+    value
+        .serialize(&mut Serializer::new(&mut bytes).compact_numbers(true))
+        .unwrap();
+
+    let mut streamed = Vec::new();
+    value
+        .serialize(&mut StreamingSerializer::from(
+            Serializer::new(&mut streamed).compact_numbers(true),
+        ))
+        .unwrap();
+    assert_eq!(streamed, bytes);
+
+    // Everything else is still narrowed
+    assert_eq!(bytes[bytes.len() - 2..], [0x11, 0x05]);
+    assert_eq!(
+        from_bytes::<(Vec<TypedArray<u64>>, TypedArray<i32>, u64)>(&bytes).unwrap(),
+        value
+    );
+}
+
+#[test]
+fn only_reads_its_own_type() {
+    let bytes = to_bytes(&vec![1.0f64]).unwrap();
+    assert!(matches!(
+        from_bytes::<TypedArray<f32>>(&bytes),
+        Err(Error::WrongType { .. })
+    ));
+    let bytes = to_bytes(&vec![1u8]).unwrap();
+    assert!(matches!(
+        from_bytes::<TypedArray<i32>>(&bytes),
+        Err(Error::WrongType { .. })
+    ));
+    assert!(matches!(
+        from_bytes::<TypedArray<f64>>(&bytes),
+        Err(Error::WrongType { .. })
+    ));
+}
+
+/// Checks that `values` compacted into an array of `header` read back as a typed array.
+fn reads_compacted<T>(values: Vec<T>, header: u8)
+where
+    T: Number + Serialize + for<'de> Deserialize<'de> + PartialEq + Debug,
+{
+    let mut bytes = Vec::new();
+    values
+        .serialize(&mut Serializer::new(&mut bytes).compact_numbers(true))
+        .unwrap();
+    assert_eq!(bytes[0], header);
+    assert_eq!(
+        from_bytes::<TypedArray<T>>(&bytes).unwrap(),
+        TypedArray(values)
+    );
+}
+
+#[test]
+fn widens_compact_arrays() {
+    reads_compacted(vec![1u32, 2, 300], 0x34);
+    reads_compacted(vec![1u64, 2, 3], 0x14);
+    reads_compacted(vec![1u128, u64::MAX as u128], 0x74);
+    reads_compacted(vec![-1i16, 2], 0x0c);
+    reads_compacted(vec![-1i64, 70000], 0x4c);
+    reads_compacted(vec![-1i128, i64::MIN as i128], 0x6c);
+    reads_compacted(vec![0.5f64, -1.5], 0x44);
+}
+
+/// Reads a typed array of `u32`s, without taking its elements.
+struct Skipped;
+
+impl<'de> Deserialize<'de> for Skipped {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SkippedVisitor;
+
+        impl<'de> Visitor<'de> for SkippedVisitor {
+            type Value = Skipped;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("anything")
+            }
+
+            fn visit_unit<E: serde::de::Error>(self) -> Result<Self::Value, E> {
+                Ok(Skipped)
+            }
+        }
+
+        deserializer.deserialize_newtype_struct("$serde_beve::TypedArray<u32>", SkippedVisitor)
+    }
+}
+
+/// Reads a typed array of `u32`s, and panics.
+struct Panics;
+
+impl<'de> Deserialize<'de> for Panics {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct PanicsVisitor;
+
+        impl<'de> Visitor<'de> for PanicsVisitor {
+            type Value = Panics;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("anything")
+            }
+
+            fn visit_unit<E: serde::de::Error>(self) -> Result<Self::Value, E> {
+                panic!("visited");
+            }
+        }
+
+        deserializer.deserialize_newtype_struct("$serde_beve::TypedArray<u32>", PanicsVisitor)
+    }
+}
+
+/// Reads a typed array of `u32`s from a unit, which only works with elements left behind.
+fn from_unit() -> Result<TypedArray<u32>, serde::de::value::Error> {
+    TypedArray::deserialize(().into_deserializer())
+}
+
+#[test]
+fn elements_are_not_left_behind() {
+    let bytes = to_bytes(&TypedSlice(&[1u32, 2])).unwrap();
+
+    from_bytes::<Skipped>(&bytes).unwrap();
+    assert!(from_unit().is_err());
+
+    assert!(std::panic::catch_unwind(|| from_bytes::<Panics>(&bytes)).is_err());
+    assert!(from_unit().is_err());
+
+    // Elements of another kind are rejected
+    let bytes = to_bytes(&TypedSlice(&[1i16, 2])).unwrap();
+    assert!(from_bytes::<TypedArray<u32>>(&bytes).is_err());
+    assert!(from_bytes::<Skipped>(&bytes).is_err());
+    assert!(from_unit().is_err());
+}
+
+#[test]
+fn human_readable_formats() {
+    let array = TypedArray(vec![1u32, 2, 300]);
+    let json = serde_json::to_string(&array).unwrap();
+    assert_eq!(json, "[1,2,300]");
+    assert_eq!(
+        serde_json::from_str::<TypedArray<u32>>(&json).unwrap(),
+        array
+    );
+    assert_eq!(
+        serde_json::to_string(&TypedSlice(&[0.5f32, -1.0])).unwrap(),
+        "[0.5,-1.0]"
+    );
+
+    // This crate writes them as typed arrays even when set to be human-readable
+    let expected = to_bytes(&array).unwrap();
+    let mut bytes = Vec::new();
+    array
+        .serialize(&mut Serializer::new(&mut bytes).human_readable(true))
+        .unwrap();
+    assert_eq!(bytes, expected);
+
+    let mut streamed = Vec::new();
+    array
+        .serialize(&mut StreamingSerializer::from(
+            Serializer::new(&mut streamed).human_readable(true),
+        ))
+        .unwrap();
+    assert_eq!(streamed, expected);
+
+    let mut deserializer = Deserializer::new(expected.as_slice()).human_readable(true);
+    assert_eq!(TypedArray::deserialize(&mut deserializer).unwrap(), array);
+}
@@ -0,0 +1,83 @@
+use serde_beve::{ArrayKind, Complex, Error, ObjectKind, Typed, Writer, to_bytes};
+use std::collections::BTreeMap;
+
+/// Returns what `write` writes.
+fn written(write: impl FnOnce(&mut Writer<&mut Vec<u8>>) -> Result<(), Error>) -> Vec<u8> {
+    let mut out = Vec::new();
+    write(&mut Writer::new(&mut out)).unwrap();
+    out
+}
+
+#[test]
+fn objects() {
+    let map = BTreeMap::from([("a", Typed(vec![1.5f32, 2.5])), ("b", Typed(vec![]))]);
+    let out = written(|w| {
+        w.begin_object(ObjectKind::String, 2)?;
+        w.write_key("a")?;
+        w.write_typed_array(&[1.5f32, 2.5])?;
+        w.write_key("b")?;
+        w.write_typed_iter(std::iter::empty::<f32>())
+    });
+    assert_eq!(out, to_bytes(&map).unwrap());
+
+    let map = BTreeMap::from([(1u16, "one"), (300, "three hundred")]);
+    let out = written(|w| {
+        w.begin_object(ObjectKind::U16, 2)?;
+        w.write_int_key(1u16)?;
+        w.write_str("one")?;
+        w.write_int_key(300u16)?;
+        w.write_str("three hundred")
+    });
+    assert_eq!(out, to_bytes(&map).unwrap());
+}
+
+#[test]
+fn arrays() {
+    let values = vec!["a", "bc"];
+    let out = written(|w| {
+        w.begin_array(ArrayKind::String, 2)?;
+        w.write_str_element("a")?;
+        w.write_str_element("bc")
+    });
+    assert_eq!(out, to_bytes(&values).unwrap());
+
+    let values = vec![true, false, true];
+    let out = written(|w| w.write_bool_array(&values));
+    assert_eq!(out, to_bytes(&values).unwrap());
+
+    let values = (1u8, "x");
+    let out = written(|w| {
+        w.begin_array(ArrayKind::Generic, 2)?;
+        w.write_number(1u8)?;
+        w.write_str("x")
+    });
+    assert_eq!(out, to_bytes(&values).unwrap());
+}
+
+#[test]
+fn complex_arrays_need_their_elements() {
+    let mut out = Vec::new();
+    let mut writer = Writer::new(&mut out);
+    assert!(matches!(
+        writer.begin_array(ArrayKind::Complex, 1),
+        Err(Error::NoArrayHeader(ArrayKind::Complex))
+    ));
+    assert!(out.is_empty());
+
+    let values = vec![Complex::new(1.0f64, 2.0)];
+    let out = written(|w| w.write_value(&values));
+    assert_eq!(out, to_bytes(&values).unwrap());
+}
+
+#[test]
+fn oversized_iterators() {
+    let mut out = Vec::new();
+    let mut writer = Writer::new(&mut out);
+    // Reports a length whose size in bytes overflows `usize`
+    let values = (0..usize::MAX / 8).map(|_| 0u128);
+    assert!(matches!(
+        writer.write_typed_iter(values),
+        Err(Error::TooLong)
+    ));
+    assert!(out.is_empty());
+}